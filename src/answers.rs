@@ -0,0 +1,209 @@
+use aoc2023::input_cache::sha256_hex;
+use aoc2023::puzzle::Answer;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// A manifest of known-good answers, keyed by year, day and part. An answer may be restricted to
+/// the input with a given SHA-256 hash, so that answers for several users' inputs can be kept in
+/// the same manifest.
+///
+/// ```toml
+/// [[answer]]
+/// year = 2023
+/// day = 1
+/// part = 1
+/// answer = "55002"
+/// input_sha256 = "..." # Optional
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct Manifest {
+    #[serde(default, rename = "answer")]
+    answers: Vec<KnownAnswer>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KnownAnswer {
+    year: u16,
+    day: u8,
+    part: u8,
+    answer: String,
+    input_sha256: Option<String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(manifest: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(manifest)?)
+    }
+
+    /// Returns whether an answer is known for the given part, for any input.
+    pub fn has_answer(&self, year: u16, day: u8, part: u8) -> bool {
+        self.answers
+            .iter()
+            .any(|known| known.year == year && known.day == day && known.part == part)
+    }
+
+    /// Returns the known answer for the given input. An answer recorded for this exact input is
+    /// preferred over one recorded without an input hash.
+    pub fn lookup(&self, year: u16, day: u8, part: u8, input: &str) -> Option<&str> {
+        let candidates: Vec<&KnownAnswer> = self
+            .answers
+            .iter()
+            .filter(|known| known.year == year && known.day == day && known.part == part)
+            .collect();
+        let input_sha256 = sha256_hex(input);
+        candidates
+            .iter()
+            .find(|known| {
+                known
+                    .input_sha256
+                    .as_ref()
+                    .is_some_and(|hash| hash.eq_ignore_ascii_case(&input_sha256))
+            })
+            .or_else(|| candidates.iter().find(|known| known.input_sha256.is_none()))
+            .map(|known| known.answer.as_str())
+    }
+}
+
+/// The result of checking an answer against the manifest.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Correct,
+    Incorrect {
+        expected: String,
+    },
+    Unknown,
+    /// The part has no answer to check.
+    NotApplicable,
+}
+
+/// Checks the actual answer against the expected answer, if one is known.
+pub fn check(expected: Option<&str>, actual: &Answer) -> Outcome {
+    if *actual == Answer::NotApplicable {
+        return Outcome::NotApplicable;
+    }
+    match expected {
+        Some(expected) if expected == actual.to_string() => Outcome::Correct,
+        Some(expected) => Outcome::Incorrect {
+            expected: expected.to_string(),
+        },
+        None => Outcome::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT_A: &str = "input a";
+    const INPUT_B: &str = "input b";
+
+    #[test]
+    fn lookup_by_year_day_and_part() {
+        let manifest = Manifest::parse(
+            r#"
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 1
+            answer = "55002"
+
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 2
+            answer = "55093"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_A), Some("55002"));
+        assert_eq!(manifest.lookup(2023, 1, 2, INPUT_A), Some("55093"));
+        assert_eq!(manifest.lookup(2023, 2, 1, INPUT_A), None);
+        assert_eq!(manifest.lookup(2022, 1, 1, INPUT_A), None);
+        assert!(manifest.has_answer(2023, 1, 2));
+        assert!(!manifest.has_answer(2023, 2, 1));
+    }
+
+    #[test]
+    fn lookup_prefers_answer_for_matching_input() {
+        let manifest = Manifest::parse(&format!(
+            r#"
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 1
+            answer = "default"
+
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 1
+            answer = "for b"
+            input_sha256 = "{}"
+            "#,
+            sha256_hex(INPUT_B).to_uppercase()
+        ))
+        .unwrap();
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_A), Some("default"));
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_B), Some("for b"));
+    }
+
+    #[test]
+    fn lookup_ignores_answers_for_other_inputs() {
+        let manifest = Manifest::parse(&format!(
+            r#"
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 1
+            answer = "for b"
+            input_sha256 = "{}"
+            "#,
+            sha256_hex(INPUT_B)
+        ))
+        .unwrap();
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_A), None);
+    }
+
+    #[test]
+    fn parse_accepts_empty_manifest() {
+        let manifest = Manifest::parse("").unwrap();
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_A), None);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_manifest() {
+        assert!(Manifest::parse("[[answer]]\nyear = 2023").is_err());
+    }
+
+    #[test]
+    fn repository_manifest_is_valid() {
+        let manifest = Manifest::load(Path::new("answers.toml")).unwrap();
+        for day in 1..=25 {
+            assert!(manifest.lookup(2023, day, 1, INPUT_A).is_some());
+        }
+        assert_eq!(manifest.lookup(2023, 1, 1, INPUT_A), Some("55002"));
+    }
+
+    #[test]
+    fn check_compares_answers() {
+        assert_eq!(check(Some("42"), &Answer::Int(42)), Outcome::Correct);
+        assert_eq!(check(Some("abc"), &Answer::from("abc")), Outcome::Correct);
+        assert_eq!(
+            check(Some("42"), &Answer::Int(43)),
+            Outcome::Incorrect {
+                expected: "42".to_string()
+            }
+        );
+        assert_eq!(check(None, &Answer::Int(42)), Outcome::Unknown);
+        assert_eq!(
+            check(Some("n/a"), &Answer::NotApplicable),
+            Outcome::NotApplicable
+        );
+    }
+}
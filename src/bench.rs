@@ -0,0 +1,425 @@
+use crate::cli::ReportFormat;
+use crate::table;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Summary statistics over the samples of a benchmark, in nanoseconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub samples: usize,
+    pub mean_ns: f64,
+    pub median_ns: f64,
+    pub stddev_ns: f64,
+    pub min_ns: f64,
+    pub max_ns: f64,
+}
+
+impl Stats {
+    /// Computes the statistics of a non-empty set of samples. The standard deviation is the sample
+    /// standard deviation.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "at least one sample is required");
+        let mut nanos: Vec<f64> = samples.iter().map(|d| d.as_nanos() as f64).collect();
+        nanos.sort_unstable_by(f64::total_cmp);
+        let n = nanos.len();
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 {
+            nanos[n / 2]
+        } else {
+            (nanos[n / 2 - 1] + nanos[n / 2]) / 2.0
+        };
+        let variance = if n > 1 {
+            nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Stats {
+            samples: n,
+            mean_ns: mean,
+            median_ns: median,
+            stddev_ns: variance.sqrt(),
+            min_ns: nanos[0],
+            max_ns: nanos[n - 1],
+        }
+    }
+}
+
+/// Runs the solver `warmup` times without measuring it, then `iterations` times measuring each run.
+/// Stops at the first run that fails, returning its error.
+pub fn measure<T, E>(
+    mut solve: impl FnMut() -> Result<T, E>,
+    warmup: usize,
+    iterations: usize,
+) -> Result<Stats, E> {
+    for _ in 0..warmup {
+        std::hint::black_box(solve()?);
+    }
+    let samples = (0..iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            std::hint::black_box(solve()?);
+            Ok(start.elapsed())
+        })
+        .collect::<Result<Vec<Duration>, E>>()?;
+    Ok(Stats::from_samples(&samples))
+}
+
+/// The result of benchmarking one part of one day.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    pub day: u8,
+    pub part: u8,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+/// A saved set of benchmarks that later runs are compared against.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Baseline {
+    pub benchmarks: Vec<Benchmark>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn find(&self, day: u8, part: u8) -> Option<&Benchmark> {
+        self.benchmarks
+            .iter()
+            .find(|benchmark| benchmark.day == day && benchmark.part == part)
+    }
+}
+
+/// How a benchmark compares to its baseline.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Verdict::Regressed => write!(f, "regressed"),
+            Verdict::Improved => write!(f, "improved"),
+            Verdict::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub baseline_mean_ns: f64,
+    /// The relative change in mean time, as a percentage of the baseline mean.
+    pub change_percent: f64,
+    pub verdict: Verdict,
+}
+
+/// Compares the current statistics with the baseline's. A change is only reported when the means
+/// differ significantly according to Welch's t-test (at the 5% level) and by more than
+/// `threshold_percent` of the baseline mean.
+pub fn compare(current: &Stats, baseline: &Stats, threshold_percent: f64) -> Comparison {
+    let difference = current.mean_ns - baseline.mean_ns;
+    let change_percent = if baseline.mean_ns > 0.0 {
+        100.0 * difference / baseline.mean_ns
+    } else {
+        0.0
+    };
+    let verdict = if is_significant(current, baseline) && change_percent.abs() > threshold_percent {
+        if difference > 0.0 {
+            Verdict::Regressed
+        } else {
+            Verdict::Improved
+        }
+    } else {
+        Verdict::Unchanged
+    };
+    Comparison {
+        baseline_mean_ns: baseline.mean_ns,
+        change_percent,
+        verdict,
+    }
+}
+
+fn is_significant(a: &Stats, b: &Stats) -> bool {
+    let var_a = a.stddev_ns.powi(2) / a.samples as f64;
+    let var_b = b.stddev_ns.powi(2) / b.samples as f64;
+    let standard_error = (var_a + var_b).sqrt();
+    if standard_error == 0.0 {
+        return a.mean_ns != b.mean_ns;
+    }
+    let t = (a.mean_ns - b.mean_ns).abs() / standard_error;
+    // Welch–Satterthwaite approximation of the degrees of freedom.
+    let df_a = if a.samples > 1 {
+        var_a.powi(2) / (a.samples - 1) as f64
+    } else {
+        0.0
+    };
+    let df_b = if b.samples > 1 {
+        var_b.powi(2) / (b.samples - 1) as f64
+    } else {
+        0.0
+    };
+    let df = if df_a + df_b > 0.0 {
+        (var_a + var_b).powi(2) / (df_a + df_b)
+    } else {
+        1.0
+    };
+    t > t_critical(df)
+}
+
+/// Returns the two-sided 95% critical value of Student's t-distribution for the given degrees of
+/// freedom, rounding the degrees of freedom down to be conservative.
+fn t_critical(df: f64) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match df.floor() as usize {
+        0 => TABLE[0],
+        df @ 1..=30 => TABLE[df - 1],
+        31..=59 => 2.021,
+        60..=119 => 2.000,
+        _ => 1.960,
+    }
+}
+
+/// Renders the benchmarks (with their comparison against the baseline, if any) in the given
+/// format.
+pub fn render(results: &[(Benchmark, Option<Comparison>)], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Table => render_table(results),
+        ReportFormat::Json => render_json(results),
+        ReportFormat::Csv => render_csv(results),
+    }
+}
+
+fn render_table(results: &[(Benchmark, Option<Comparison>)]) -> String {
+    let headers = [
+        "Day", "Part", "Samples", "Mean", "Median", "Std Dev", "Min", "Baseline", "Change",
+        "Verdict",
+    ];
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|(benchmark, comparison)| {
+            let stats = &benchmark.stats;
+            let mut row = vec![
+                format!("{:02}", benchmark.day),
+                benchmark.part.to_string(),
+                stats.samples.to_string(),
+                format_nanos(stats.mean_ns),
+                format_nanos(stats.median_ns),
+                format_nanos(stats.stddev_ns),
+                format_nanos(stats.min_ns),
+            ];
+            match comparison {
+                Some(comparison) => row.extend([
+                    format_nanos(comparison.baseline_mean_ns),
+                    format!("{:+.1}%", comparison.change_percent),
+                    comparison.verdict.to_string(),
+                ]),
+                None => row.extend([String::new(), String::new(), String::new()]),
+            }
+            row
+        })
+        .collect();
+    table::render(&headers, &rows)
+}
+
+fn render_json(results: &[(Benchmark, Option<Comparison>)]) -> String {
+    #[derive(Serialize)]
+    struct Result<'a> {
+        #[serde(flatten)]
+        benchmark: &'a Benchmark,
+        comparison: &'a Option<Comparison>,
+    }
+    let results: Vec<Result> = results
+        .iter()
+        .map(|(benchmark, comparison)| Result {
+            benchmark,
+            comparison,
+        })
+        .collect();
+    serde_json::to_string_pretty(&results).unwrap()
+}
+
+fn render_csv(results: &[(Benchmark, Option<Comparison>)]) -> String {
+    let headers = [
+        "day",
+        "part",
+        "samples",
+        "mean_ns",
+        "median_ns",
+        "stddev_ns",
+        "min_ns",
+        "max_ns",
+        "baseline_mean_ns",
+        "change_percent",
+        "verdict",
+    ];
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|(benchmark, comparison)| {
+            let stats = &benchmark.stats;
+            let mut row = vec![
+                benchmark.day.to_string(),
+                benchmark.part.to_string(),
+                stats.samples.to_string(),
+                format!("{:.0}", stats.mean_ns),
+                format!("{:.0}", stats.median_ns),
+                format!("{:.0}", stats.stddev_ns),
+                format!("{:.0}", stats.min_ns),
+                format!("{:.0}", stats.max_ns),
+            ];
+            match comparison {
+                Some(comparison) => row.extend([
+                    format!("{:.0}", comparison.baseline_mean_ns),
+                    format!("{:.2}", comparison.change_percent),
+                    comparison.verdict.to_string(),
+                ]),
+                None => row.extend([String::new(), String::new(), String::new()]),
+            }
+            row
+        })
+        .collect();
+    table::render_csv(&headers, &rows)
+}
+
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.2?}", Duration::from_nanos(nanos.round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn stats_from_samples() {
+        let samples: Vec<Duration> = [4, 1, 3, 2].into_iter().map(Duration::from_nanos).collect();
+        let stats = Stats::from_samples(&samples);
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.mean_ns, 2.5);
+        assert_eq!(stats.median_ns, 2.5);
+        assert_eq!(stats.min_ns, 1.0);
+        assert_eq!(stats.max_ns, 4.0);
+        assert!((stats.stddev_ns - (5.0f64 / 3.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stats_from_single_sample() {
+        let stats = Stats::from_samples(&[Duration::from_nanos(7)]);
+        assert_eq!(stats.median_ns, 7.0);
+        assert_eq!(stats.stddev_ns, 0.0);
+    }
+
+    #[test]
+    fn measure_runs_warmup_and_iterations() {
+        let mut calls = 0;
+        let stats = measure(
+            || {
+                calls += 1;
+                Ok::<_, ()>(calls)
+            },
+            3,
+            5,
+        )
+        .unwrap();
+        assert_eq!(calls, 8);
+        assert_eq!(stats.samples, 5);
+    }
+
+    #[test]
+    fn measure_stops_at_first_error() {
+        let mut calls = 0;
+        let result = measure(
+            || {
+                calls += 1;
+                if calls < 2 {
+                    Ok(calls)
+                } else {
+                    Err("failed")
+                }
+            },
+            3,
+            5,
+        );
+        assert_eq!(result, Err("failed"));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn compare_flags_significant_regression() {
+        let baseline = stats(100.0, 2.0, 20);
+        let current = stats(150.0, 2.0, 20);
+        let comparison = compare(&current, &baseline, 5.0);
+        assert_eq!(comparison.verdict, Verdict::Regressed);
+        assert_eq!(comparison.change_percent, 50.0);
+        assert_eq!(compare(&baseline, &current, 5.0).verdict, Verdict::Improved);
+    }
+
+    #[test]
+    fn compare_ignores_noise() {
+        // Means differ by 10% but the spread is far too large for that to be significant.
+        let baseline = stats(100.0, 50.0, 5);
+        let current = stats(110.0, 50.0, 5);
+        assert_eq!(
+            compare(&current, &baseline, 5.0).verdict,
+            Verdict::Unchanged
+        );
+    }
+
+    #[test]
+    fn compare_ignores_changes_below_threshold() {
+        let baseline = stats(100.0, 0.1, 100);
+        let current = stats(102.0, 0.1, 100);
+        assert_eq!(
+            compare(&current, &baseline, 5.0).verdict,
+            Verdict::Unchanged
+        );
+        assert_eq!(
+            compare(&current, &baseline, 1.0).verdict,
+            Verdict::Regressed
+        );
+    }
+
+    #[test]
+    fn baseline_round_trips_through_file() {
+        let baseline = Baseline {
+            benchmarks: vec![Benchmark {
+                day: 23,
+                part: 2,
+                stats: stats(1e9, 1e6, 10),
+            }],
+        };
+        let file = NamedTempFile::new().unwrap();
+        baseline.save(file.path()).unwrap();
+        let loaded = Baseline::load(file.path()).unwrap();
+        assert_eq!(loaded, baseline);
+        assert!(loaded.find(23, 2).is_some());
+        assert!(loaded.find(23, 1).is_none());
+    }
+
+    fn stats(mean_ns: f64, stddev_ns: f64, samples: usize) -> Stats {
+        Stats {
+            samples,
+            mean_ns,
+            median_ns: mean_ns,
+            stddev_ns,
+            min_ns: mean_ns - stddev_ns,
+            max_ns: mean_ns + stddev_ns,
+        }
+    }
+}
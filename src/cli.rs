@@ -0,0 +1,444 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// Advent of Code 2023 solutions.
+#[derive(Parser)]
+#[command(name = "aoc2023")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// The year of the puzzles to work on. Defaults to the latest year with solutions.
+    #[arg(short, long, global = true)]
+    pub year: Option<u16>,
+
+    /// Reads the session token from this file unless the AOC_SESSION environment variable is set.
+    #[arg(long, global = true)]
+    pub session_file: Option<PathBuf>,
+
+    /// Waits for a puzzle that has not unlocked yet instead of failing.
+    #[arg(long, global = true)]
+    pub wait_for_unlock: bool,
+
+    /// Encrypts cached inputs with a key derived from this file, and decrypts encrypted inputs and
+    /// session token files with it. Overrides AOC_STORE_KEY_FILE and AOC_STORE_PASSPHRASE.
+    #[arg(long, global = true)]
+    pub key_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Solves the selected days and prints their answers.
+    Run(RunArgs),
+    /// Repeatedly solves the selected days and reports timing statistics.
+    Bench(BenchArgs),
+    /// Solves the selected days and checks the answers against a manifest of known answers.
+    Verify(VerifyArgs),
+    /// Extracts the example inputs and answers from the puzzle descriptions into test fixtures.
+    Examples(ExamplesArgs),
+    /// Checks that the session token is accepted by the Advent of Code website.
+    CheckToken,
+    /// Fetches the inputs of the selected days again, replacing the cached copies.
+    Refetch(RefetchArgs),
+    /// Shows the standings of a private leaderboard, with when each member got each star.
+    Leaderboard(LeaderboardArgs),
+    /// Compares the stars on the website's calendar with the days that have solutions and
+    /// recorded answers.
+    Progress(ProgressArgs),
+    /// Encrypts files (such as inputs or the session token) in place with the configured key.
+    Encrypt(CryptArgs),
+    /// Decrypts files encrypted with the configured key in place.
+    Decrypt(CryptArgs),
+}
+
+/// Selects which days and parts a command operates on.
+#[derive(Args, Default)]
+pub struct Selection {
+    /// The day to solve (may be repeated). Solves every day if omitted.
+    #[arg(short, long = "day", value_parser = clap::value_parser!(u8).range(1..=25))]
+    pub days: Vec<u8>,
+
+    /// The part to solve. Solves both parts if omitted.
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+
+    /// Reads the puzzle input from this file ('-' for stdin) instead of the input fetcher.
+    /// Requires exactly one day.
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Args, Default)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// Prints a report of how long parsing and each part took instead of the plain answers.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "table")]
+    pub timings: Option<ReportFormat>,
+
+    /// Solves the days concurrently, printing the results in day order once all are solved.
+    #[arg(long)]
+    pub parallel: bool,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// The number of unmeasured runs before sampling begins.
+    #[arg(long, default_value_t = 3)]
+    pub warmup: usize,
+
+    /// The number of measured runs.
+    #[arg(long, default_value_t = 10, value_parser = parse_iterations)]
+    pub iterations: usize,
+
+    /// Compares the results against the baseline saved at this path.
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Saves the results as a baseline at this path.
+    #[arg(long)]
+    pub save_baseline: Option<PathBuf>,
+
+    /// The smallest change in mean time (as a percentage) that is reported against the baseline.
+    #[arg(long, default_value_t = 5.0)]
+    pub threshold: f64,
+
+    /// The format of the report.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// The manifest of known answers.
+    #[arg(long, default_value = "answers.toml")]
+    pub answers: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ExamplesArgs {
+    /// The day to extract examples for (may be repeated). Extracts every day's if omitted.
+    #[arg(short, long = "day", value_parser = clap::value_parser!(u8).range(1..=25))]
+    pub days: Vec<u8>,
+
+    /// The directory the fixtures are written to.
+    #[arg(long, default_value = aoc2023::examples::FIXTURE_DIR)]
+    pub dir: PathBuf,
+}
+
+#[derive(Args)]
+pub struct RefetchArgs {
+    /// The day to refetch (may be repeated). Refetches every day whose cached input is corrupt if
+    /// omitted.
+    #[arg(short, long = "day", value_parser = clap::value_parser!(u8).range(1..=25))]
+    pub days: Vec<u8>,
+}
+
+#[derive(Args)]
+pub struct LeaderboardArgs {
+    /// The ID of the leaderboard, which is the number in its URL.
+    #[arg(long)]
+    pub id: u64,
+
+    /// How the leaderboard is printed.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+}
+
+#[derive(Args)]
+pub struct ProgressArgs {
+    /// The manifest of known answers.
+    #[arg(long, default_value = "answers.toml")]
+    pub answers: PathBuf,
+
+    /// Rewrites the star and completed-day badges in this README (by default README.md).
+    #[arg(long, num_args = 0..=1, default_missing_value = "README.md")]
+    pub update_readme: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct CryptArgs {
+    /// The files to encrypt or decrypt.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+}
+
+/// The format of a report printed by the runner.
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+pub enum ReportFormat {
+    /// An aligned plain-text table.
+    Table,
+    /// A JSON document.
+    Json,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+impl Cli {
+    /// Returns the command to execute. Running without a subcommand solves every day.
+    pub fn command(self) -> Command {
+        self.command
+            .unwrap_or_else(|| Command::Run(RunArgs::default()))
+    }
+}
+
+fn parse_iterations(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(iterations) => Ok(iterations),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(std::iter::once("aoc2023").chain(args.iter().copied()))
+            .map(Cli::command)
+    }
+
+    fn parse_run(args: &[&str]) -> RunArgs {
+        match parse(args).unwrap() {
+            Command::Run(args) => args,
+            _ => panic!("expected the run command"),
+        }
+    }
+
+    fn parse_bench(args: &[&str]) -> BenchArgs {
+        match parse(args).unwrap() {
+            Command::Bench(args) => args,
+            _ => panic!("expected the bench command"),
+        }
+    }
+
+    fn parse_verify(args: &[&str]) -> VerifyArgs {
+        match parse(args).unwrap() {
+            Command::Verify(args) => args,
+            _ => panic!("expected the verify command"),
+        }
+    }
+
+    fn parse_examples(args: &[&str]) -> ExamplesArgs {
+        match parse(args).unwrap() {
+            Command::Examples(args) => args,
+            _ => panic!("expected the examples command"),
+        }
+    }
+
+    #[test]
+    fn no_arguments_runs_everything() {
+        let args = parse_run(&[]);
+        assert!(args.selection.days.is_empty());
+        assert_eq!(args.selection.part, None);
+        assert_eq!(args.selection.input, None);
+        assert_eq!(args.timings, None);
+        assert!(!args.parallel);
+    }
+
+    #[test]
+    fn run_accepts_day_part_and_input() {
+        let args = parse_run(&["run", "--day", "17", "--part", "2", "--input", "-"]);
+        assert_eq!(args.selection.days, vec![17]);
+        assert_eq!(args.selection.part, Some(2));
+        assert_eq!(args.selection.input, Some(PathBuf::from("-")));
+    }
+
+    #[test]
+    fn run_accepts_repeated_days() {
+        let args = parse_run(&["run", "-d", "1", "-d", "25"]);
+        assert_eq!(args.selection.days, vec![1, 25]);
+    }
+
+    #[test]
+    fn run_accepts_timings_format() {
+        let args = parse_run(&["run", "--timings"]);
+        assert_eq!(args.timings, Some(ReportFormat::Table));
+        let args = parse_run(&["run", "--timings", "json"]);
+        assert_eq!(args.timings, Some(ReportFormat::Json));
+        let args = parse_run(&["run", "--timings=csv", "--day", "3"]);
+        assert_eq!(args.timings, Some(ReportFormat::Csv));
+        assert_eq!(args.selection.days, vec![3]);
+    }
+
+    #[test]
+    fn run_accepts_parallel() {
+        let args = parse_run(&["run", "--parallel", "--timings"]);
+        assert!(args.parallel);
+        assert_eq!(args.timings, Some(ReportFormat::Table));
+    }
+
+    #[test]
+    fn run_rejects_out_of_range_values() {
+        assert!(parse(&["run", "--day", "0"]).is_err());
+        assert!(parse(&["run", "--day", "26"]).is_err());
+        assert!(parse(&["run", "--part", "3"]).is_err());
+    }
+
+    #[test]
+    fn bench_has_defaults() {
+        let args = parse_bench(&["bench", "--day", "23"]);
+        assert_eq!(args.selection.days, vec![23]);
+        assert_eq!(args.warmup, 3);
+        assert_eq!(args.iterations, 10);
+        assert_eq!(args.baseline, None);
+        assert_eq!(args.save_baseline, None);
+        assert_eq!(args.threshold, 5.0);
+        assert_eq!(args.format, ReportFormat::Table);
+    }
+
+    #[test]
+    fn bench_accepts_options() {
+        let args = parse_bench(&[
+            "bench",
+            "--warmup",
+            "0",
+            "--iterations",
+            "50",
+            "--baseline",
+            "old.json",
+            "--save-baseline",
+            "new.json",
+            "--threshold",
+            "2.5",
+            "--format",
+            "csv",
+        ]);
+        assert_eq!(args.warmup, 0);
+        assert_eq!(args.iterations, 50);
+        assert_eq!(args.baseline, Some(PathBuf::from("old.json")));
+        assert_eq!(args.save_baseline, Some(PathBuf::from("new.json")));
+        assert_eq!(args.threshold, 2.5);
+        assert_eq!(args.format, ReportFormat::Csv);
+    }
+
+    #[test]
+    fn bench_rejects_zero_iterations() {
+        assert!(parse(&["bench", "--iterations", "0"]).is_err());
+    }
+
+    #[test]
+    fn verify_defaults_to_answers_toml() {
+        let args = parse_verify(&["verify"]);
+        assert_eq!(args.answers, PathBuf::from("answers.toml"));
+        let args = parse_verify(&["verify", "--answers", "other.toml", "--day", "5"]);
+        assert_eq!(args.answers, PathBuf::from("other.toml"));
+        assert_eq!(args.selection.days, vec![5]);
+    }
+
+    #[test]
+    fn session_file_is_accepted_with_any_command() {
+        let cli =
+            Cli::try_parse_from(["aoc2023", "check-token", "--session-file", "s.txt"]).unwrap();
+        assert_eq!(cli.session_file, Some(PathBuf::from("s.txt")));
+        assert!(matches!(cli.command(), Command::CheckToken));
+        let cli = Cli::try_parse_from(["aoc2023", "--session-file", "s.txt"]).unwrap();
+        assert_eq!(cli.session_file, Some(PathBuf::from("s.txt")));
+        assert!(Cli::try_parse_from(["aoc2023"])
+            .unwrap()
+            .session_file
+            .is_none());
+    }
+
+    #[test]
+    fn wait_for_unlock_is_accepted_with_any_command() {
+        let cli =
+            Cli::try_parse_from(["aoc2023", "run", "--day", "1", "--wait-for-unlock"]).unwrap();
+        assert!(cli.wait_for_unlock);
+        assert!(!Cli::try_parse_from(["aoc2023"]).unwrap().wait_for_unlock);
+    }
+
+    #[test]
+    fn year_is_accepted_with_any_command() {
+        let cli = Cli::try_parse_from(["aoc2023", "--year", "2022", "run", "-d", "1"]).unwrap();
+        assert_eq!(cli.year, Some(2022));
+        let cli = Cli::try_parse_from(["aoc2023", "verify", "-y", "2023"]).unwrap();
+        assert_eq!(cli.year, Some(2023));
+        assert_eq!(Cli::try_parse_from(["aoc2023"]).unwrap().year, None);
+    }
+
+    #[test]
+    fn leaderboard_requires_id() {
+        match parse(&["leaderboard", "--id", "1001", "--format", "json"]).unwrap() {
+            Command::Leaderboard(args) => {
+                assert_eq!(args.id, 1001);
+                assert_eq!(args.format, ReportFormat::Json);
+            }
+            _ => panic!("expected the leaderboard command"),
+        }
+        assert!(
+            matches!(parse(&["leaderboard", "--id", "7"]).unwrap(), Command::Leaderboard(args) if args.format == ReportFormat::Table)
+        );
+        assert!(parse(&["leaderboard"]).is_err());
+    }
+
+    #[test]
+    fn progress_optionally_updates_readme() {
+        match parse(&["progress"]).unwrap() {
+            Command::Progress(args) => {
+                assert_eq!(args.answers, PathBuf::from("answers.toml"));
+                assert_eq!(args.update_readme, None);
+            }
+            _ => panic!("expected the progress command"),
+        }
+        match parse(&["progress", "--update-readme"]).unwrap() {
+            Command::Progress(args) => {
+                assert_eq!(args.update_readme, Some(PathBuf::from("README.md")))
+            }
+            _ => panic!("expected the progress command"),
+        }
+        match parse(&["progress", "--update-readme", "docs/README.md"]).unwrap() {
+            Command::Progress(args) => {
+                assert_eq!(args.update_readme, Some(PathBuf::from("docs/README.md")))
+            }
+            _ => panic!("expected the progress command"),
+        }
+    }
+
+    #[test]
+    fn encrypt_requires_paths() {
+        match parse(&["encrypt", "resources/tests/01", "cookie.txt"]).unwrap() {
+            Command::Encrypt(args) => assert_eq!(
+                args.paths,
+                vec![
+                    PathBuf::from("resources/tests/01"),
+                    PathBuf::from("cookie.txt")
+                ]
+            ),
+            _ => panic!("expected the encrypt command"),
+        }
+        assert!(parse(&["decrypt"]).is_err());
+        let cli = Cli::try_parse_from(["aoc2023", "decrypt", "01", "--key-file", "key"]).unwrap();
+        assert_eq!(cli.key_file, Some(PathBuf::from("key")));
+    }
+
+    #[test]
+    fn refetch_accepts_days() {
+        match parse(&["refetch", "-d", "3", "-d", "4"]).unwrap() {
+            Command::Refetch(args) => assert_eq!(args.days, vec![3, 4]),
+            _ => panic!("expected the refetch command"),
+        }
+        assert!(
+            matches!(parse(&["refetch"]).unwrap(), Command::Refetch(args) if args.days.is_empty())
+        );
+    }
+
+    #[test]
+    fn examples_defaults_to_fixture_dir() {
+        let args = parse_examples(&["examples"]);
+        assert!(args.days.is_empty());
+        assert_eq!(args.dir, PathBuf::from("resources/examples"));
+        let args = parse_examples(&["examples", "-d", "9", "--dir", "fixtures"]);
+        assert_eq!(args.days, vec![9]);
+        assert_eq!(args.dir, PathBuf::from("fixtures"));
+        assert!(parse(&["examples", "--day", "26"]).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use std::sync::Mutex;
+
+/// A source of the current time, which can be replaced in tests.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Blocks for the given duration.
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration.to_std().unwrap_or_default());
+    }
+}
+
+/// The system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when slept on. Used for testing.
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+/// Returns when the given day of an event unlocks: midnight US-Eastern on that day of December.
+/// Eastern time is always standard time (UTC-5) in December. Returns `None` if there is no such
+/// day in the event.
+pub fn unlock_time(year: u16, day: u8) -> Option<DateTime<Utc>> {
+    if !(1..=25).contains(&day) {
+        return None;
+    }
+    let eastern = FixedOffset::west_opt(5 * 3600).unwrap();
+    let midnight = NaiveDate::from_ymd_opt(year as i32, 12, day as u32)?
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    Some(
+        eastern
+            .from_local_datetime(&midnight)
+            .unwrap()
+            .with_timezone(&Utc),
+    )
+}
+
+/// Formats a duration as days, hours, minutes and seconds, omitting leading zero units.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let units = [
+        (seconds / 86400, "d"),
+        (seconds / 3600 % 24, "h"),
+        (seconds / 60 % 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    let first = units
+        .iter()
+        .position(|&(value, _)| value > 0)
+        .unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlock_time_is_midnight_eastern() {
+        assert_eq!(
+            unlock_time(2023, 1).unwrap().to_rfc3339(),
+            "2023-12-01T05:00:00+00:00"
+        );
+        assert_eq!(
+            unlock_time(2023, 25).unwrap().to_rfc3339(),
+            "2023-12-25T05:00:00+00:00"
+        );
+        assert_eq!(unlock_time(2023, 0), None);
+        assert_eq!(unlock_time(2023, 26), None);
+        assert_eq!(unlock_time(2023, 32), None);
+    }
+
+    #[test]
+    fn format_duration_omits_leading_zero_units() {
+        assert_eq!(format_duration(Duration::seconds(0)), "0s");
+        assert_eq!(format_duration(Duration::seconds(59)), "59s");
+        assert_eq!(format_duration(Duration::seconds(3600)), "1h 0m 0s");
+        assert_eq!(format_duration(Duration::seconds(90061)), "1d 1h 1m 1s");
+        assert_eq!(format_duration(Duration::seconds(-5)), "0s");
+    }
+
+    #[test]
+    fn fixed_clock_advances_when_slept_on() {
+        let clock = FixedClock::new(unlock_time(2023, 1).unwrap());
+        clock.sleep(Duration::days(1));
+        assert_eq!(Some(clock.now()), unlock_time(2023, 2));
+    }
+}
@@ -0,0 +1,289 @@
+use num::Integer;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Where a sequence of states starts repeating: the state at index `start + length` is the same as
+/// the one at `start`, and so on forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The index of the first state in the cycle.
+    pub start: usize,
+    /// The number of states in the cycle.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Returns the smallest index whose state is the same as the state at index `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Returns a hash of a value, to compare states by when comparing them whole is too slow. Distinct
+/// states are very unlikely to have the same fingerprint, but it is possible.
+pub fn fingerprint<T: Hash + ?Sized>(value: &T) -> u64 {
+    // The default hasher always uses the same keys, so fingerprints are reproducible.
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Finds the cycle of the states reached from `initial` by repeatedly calling `step`, comparing
+/// states by `key`, using Brent's algorithm. This keeps only a few states at a time, and steps
+/// fewer times than Floyd's algorithm. Never returns if the states do not repeat.
+pub fn brent<S: Clone, K: PartialEq>(
+    initial: &S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+) -> Cycle {
+    // Find the length of the cycle: the hare goes ahead one step at a time, and the tortoise
+    // jumps to it whenever it has gone a power of two steps without meeting it.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = key(initial);
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while tortoise != key(&hare) {
+        if power == length {
+            tortoise = key(&hare);
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    // Find the start of the cycle: with the hare a cycle length ahead, they first meet there.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Finds the cycle of the states reached from `initial` by repeatedly calling `step`, comparing
+/// whole states, using Brent's algorithm. Unlike comparing fingerprints this cannot mistake two
+/// distinct states for the same one, and it only clones a state at each power of two steps.
+/// Never returns if the states do not repeat.
+pub fn brent_exact<S: Clone + PartialEq>(initial: &S, mut step: impl FnMut(&mut S)) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while tortoise != hare {
+        if power == length {
+            tortoise.clone_from(&hare);
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Finds the cycle of the states reached from `initial` by repeatedly calling `step`, comparing
+/// states by `key`, using Floyd's algorithm. Never returns if the states do not repeat.
+pub fn floyd<S: Clone, K: PartialEq>(
+    initial: &S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+) -> Cycle {
+    // The hare goes twice as fast as the tortoise until they meet inside the cycle.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut tortoise);
+    step(&mut hare);
+    step(&mut hare);
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise);
+        step(&mut hare);
+        step(&mut hare);
+    }
+
+    // The distance from the initial state to the start of the cycle is the same as from the
+    // meeting point onwards to the start.
+    let mut tortoise = initial.clone();
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    step(&mut hare);
+    while key(&tortoise) != key(&hare) {
+        step(&mut hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Returns the state after `n` steps from `initial`, skipping over whole cycles so that it takes
+/// time proportional to the length of the cycle rather than to `n`.
+pub fn nth_state<S: Clone + PartialEq>(initial: &S, mut step: impl FnMut(&mut S), n: usize) -> S {
+    let cycle = brent_exact(initial, &mut step);
+    advance(initial, step, cycle.reduce(n))
+}
+
+/// Returns the state after `n` steps from `initial` like [`nth_state`], but comparing states by
+/// `key`, such as their [`fingerprint`], for states that are slow to compare whole.
+pub fn nth_state_by_key<S: Clone, K: PartialEq>(
+    initial: &S,
+    mut step: impl FnMut(&mut S),
+    key: impl FnMut(&S) -> K,
+    n: usize,
+) -> S {
+    let cycle = brent(initial, &mut step, key);
+    advance(initial, step, cycle.reduce(n))
+}
+
+fn advance<S: Clone>(initial: &S, mut step: impl FnMut(&mut S), steps: usize) -> S {
+    let mut state = initial.clone();
+    for _ in 0..steps {
+        step(&mut state);
+    }
+    state
+}
+
+/// The times at which something happens periodically: `offset`, `offset + period`,
+/// `offset + 2 * period` and so on. The period must not be zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Periodic {
+    pub offset: u64,
+    pub period: u64,
+}
+
+impl Periodic {
+    pub fn new(offset: u64, period: u64) -> Self {
+        assert!(period > 0, "the period must not be zero");
+        Self { offset, period }
+    }
+
+    pub fn contains(&self, time: u64) -> bool {
+        time >= self.offset && (time - self.offset).is_multiple_of(self.period)
+    }
+}
+
+/// Returns the times at which all of the periodic things happen together, by the Chinese
+/// remainder theorem generalised to periods that are not coprime. Returns `None` if they never
+/// happen together or the first such time does not fit in a u64.
+pub fn align(sequences: &[Periodic]) -> Option<Periodic> {
+    // Solve for the remainder modulo the combined period first, then find the first time at
+    // which every sequence has started.
+    let (mut remainder, mut period) = (0u64, 1u64);
+    for sequence in sequences {
+        let (n, b) = (sequence.period, sequence.offset % sequence.period);
+        // Find x = remainder + period * t with x = b modulo n, where
+        // period * t = b - remainder modulo n has a solution only if gcd(period, n) divides the
+        // right-hand side.
+        let egcd = (period as i128).extended_gcd(&(n as i128));
+        let gcd = egcd.gcd as u128;
+        let diff = (b as i128 - remainder as i128).rem_euclid(n as i128) as u128;
+        if !diff.is_multiple_of(gcd) {
+            return None;
+        }
+        let modulus = n as u128 / gcd;
+        let inverse = egcd.x.rem_euclid(modulus as i128) as u128;
+        let t = (diff / gcd) % modulus * inverse % modulus;
+        let combined = period as u128 * modulus;
+        remainder = ((remainder as u128 + period as u128 * t) % combined)
+            .try_into()
+            .ok()?;
+        period = combined.try_into().ok()?;
+    }
+    let start = sequences.iter().map(|sequence| sequence.offset).max()?;
+    let offset = if remainder >= start {
+        remainder
+    } else {
+        let periods = (start - remainder).div_ceil(period);
+        periods
+            .checked_mul(period)
+            .and_then(|skip| skip.checked_add(remainder))?
+    };
+    Some(Periodic { offset, period })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps a number through a sequence that runs 0, 1, 2, 3, 4, 5, 2, 3, 4, 5, 2...
+    fn step(n: &mut u32) {
+        *n = if *n == 5 { 2 } else { *n + 1 };
+    }
+
+    fn cycle(start: usize, length: usize) -> Cycle {
+        Cycle { start, length }
+    }
+
+    #[test]
+    fn brent_and_floyd_find_the_same_cycle() {
+        assert_eq!(brent(&0, step, |&n| n), cycle(2, 4));
+        assert_eq!(brent_exact(&0, step), cycle(2, 4));
+        assert_eq!(floyd(&0, step, |&n| n), cycle(2, 4));
+        assert_eq!(brent(&3, step, fingerprint), cycle(0, 4));
+        assert_eq!(floyd(&2, step, |&n| n), cycle(0, 4));
+    }
+
+    #[test]
+    fn nth_state_skips_whole_cycles() {
+        assert_eq!(cycle(2, 4).reduce(1), 1);
+        assert_eq!(cycle(2, 4).reduce(10), 2);
+        let mut steps = 0;
+        let count_steps = |n: &mut u32| {
+            steps += 1;
+            step(n);
+        };
+        assert_eq!(nth_state(&0, count_steps, 1_000_000_001), 5);
+        assert!(steps < 100);
+        assert_eq!(nth_state_by_key(&0, step, fingerprint, 1_000_000_001), 5);
+    }
+
+    #[test]
+    fn align_combines_offsets_and_periods() {
+        // Times 3, 8, 13, ... and 1, 7, 13, ... first meet at 13, then every 30.
+        let aligned = align(&[Periodic::new(3, 5), Periodic::new(1, 6)]).unwrap();
+        assert_eq!(aligned, Periodic::new(13, 30));
+        // Periods that share a factor.
+        let aligned = align(&[Periodic::new(2, 4), Periodic::new(0, 6)]).unwrap();
+        assert_eq!(aligned, Periodic::new(6, 12));
+        assert!(aligned.contains(18) && !aligned.contains(12));
+        // Everything has started by the first aligned time, even if the remainders agree earlier.
+        assert_eq!(
+            align(&[Periodic::new(10, 2), Periodic::new(0, 4)]),
+            Some(Periodic::new(12, 4))
+        );
+        assert_eq!(align(&[Periodic::new(1, 4), Periodic::new(0, 6)]), None);
+        assert_eq!(align(&[]), None);
+        assert_eq!(
+            align(&[Periodic::new(u64::MAX - 1, u64::MAX), Periodic::new(0, 2)]),
+            None
+        );
+    }
+}
@@ -1,39 +1,42 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 1;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.solve_internal(false).to_string()
-    }
+pub struct Day;
+
+impl Solver for Day {
+    /// The calibration document, which each part reads digits from differently.
+    type Model = String;
 
-    fn solve_part_2(&self) -> String {
-        self.solve_internal(true).to_string()
+    fn parse(input: &str) -> Result<String, PuzzleError> {
+        Ok(input.to_string())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_1(document: &String) -> Result<Answer, PuzzleError> {
+        Ok(sum_calibration_values(document, false)?.into())
     }
 
-    fn solve_internal(&self, allow_spelled_out: bool) -> i32 {
-        self.input
-            .lines()
-            .map(|line| extract_calibration_value(extract_digits(line, allow_spelled_out)))
-            .sum()
+    fn solve_part_2(document: &String) -> Result<Answer, PuzzleError> {
+        Ok(sum_calibration_values(document, true)?.into())
     }
 }
 
-fn extract_calibration_value(digits: Vec<i32>) -> i32 {
-    let first_digit = digits.first().unwrap();
-    let last_digit = digits.last().unwrap();
-    (10 * first_digit) + last_digit
+pub fn sum_calibration_values(document: &str, allow_spelled_out: bool) -> Result<i32, PuzzleError> {
+    let input = Input::new(DAY, document);
+    input
+        .lines()
+        .map(|line| {
+            extract_calibration_value(extract_digits(line, allow_spelled_out))
+                .ok_or_else(|| input.error(line, "line contains no digits"))
+        })
+        .sum()
+}
+
+fn extract_calibration_value(digits: Vec<i32>) -> Option<i32> {
+    let first_digit = digits.first()?;
+    let last_digit = digits.last()?;
+    Some((10 * first_digit) + last_digit)
 }
 
 fn extract_digits(line: &str, allow_spelled_out: bool) -> Vec<i32> {
@@ -55,7 +58,7 @@ fn extract_digits(line: &str, allow_spelled_out: bool) -> Vec<i32> {
                 "seven" => 7,
                 "eight" => 8,
                 "nine" => 9,
-                _ => m.as_str().parse::<i32>().unwrap(),
+                digit => digit.as_bytes()[0] as i32 - '0' as i32,
             };
             digits.push(digit);
         }
@@ -75,15 +78,16 @@ mod tests {
             pqr3stu8vwx\n\
             a1b2c3d4e5f\n\
             treb7uchet";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "142");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(142));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/01")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "55002");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/01")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(55002));
     }
 
     #[test]
@@ -96,14 +100,24 @@ mod tests {
             4nineeightseven2\n\
             zoneight234\n\
             7pqrstsixteen";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "281");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(281));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/01")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "55093");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/01")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(55093));
+    }
+
+    #[test]
+    fn test_line_without_digits() {
+        let puzzle = Day::create("1abc2\nabc").unwrap();
+        assert_eq!(
+            puzzle.solve_part_1().unwrap_err().to_string(),
+            "Day 01, line 2, column 1: line contains no digits (found 'abc')"
+        );
     }
 }
@@ -1,15 +1,22 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 2;
+
+pub struct Day;
+
+impl Solver for Day {
+    /// The reveals of each game, in game order.
+    type Model = Vec<Vec<Colors>>;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        let input = Input::new(DAY, input);
+        input.lines().map(|line| parse_game(input, line)).collect()
+    }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.input
-            .lines()
-            .map(parse_game)
+    fn solve_part_1(games: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(games
+            .iter()
             .enumerate()
             .filter_map(|(i, game)| {
                 if game
@@ -22,13 +29,12 @@ impl Puzzle for Day {
                 }
             })
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 
-    fn solve_part_2(&self) -> String {
-        self.input
-            .lines()
-            .map(parse_game)
+    fn solve_part_2(games: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(games
+            .iter()
             .map(|game| {
                 game.iter()
                     .fold((0, 0, 0), |(max_red, max_green, max_blue), reveal| {
@@ -41,39 +47,30 @@ impl Puzzle for Day {
             })
             .map(|(max_red, max_green, max_blue)| max_red * max_green * max_blue)
             .sum::<i32>()
-            .to_string()
-    }
-}
-
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+            .into())
     }
 }
 
-struct Colors {
-    red: i32,
-    green: i32,
-    blue: i32,
+pub struct Colors {
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
 }
 
-fn parse_game(line: &str) -> Vec<Colors> {
+fn parse_game(input: Input, line: &str) -> Result<Vec<Colors>, PuzzleError> {
     line.split(';')
         .map(|reveal| {
             let re = regex!(r"(\d+) (red|green|blue)");
             let (mut red, mut green, mut blue) = (0, 0, 0);
             for cap in re.captures_iter(reveal) {
-                let count = cap[1].parse::<i32>().unwrap();
+                let count = input.parse::<i32>(cap.get(1).unwrap().as_str())?;
                 match &cap[2] {
                     "red" => red += count,
                     "green" => green += count,
-                    "blue" => blue += count,
-                    _ => unreachable!(),
+                    _ => blue += count,
                 }
             }
-            Colors { red, green, blue }
+            Ok(Colors { red, green, blue })
         })
         .collect()
 }
@@ -90,15 +87,16 @@ mod tests {
             Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n\
             Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n\
             Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "8");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(8));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/02")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "1734");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/02")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(1734));
     }
 
     #[test]
@@ -108,14 +106,15 @@ mod tests {
             Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n\
             Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n\
             Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "2286");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(2286));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/02")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "70387");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/02")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(70387));
     }
 }
@@ -1,23 +1,30 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex;
 use std::collections::{HashMap, HashSet};
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 3;
+
+pub struct Day;
+
+impl Solver for Day {
+    /// The part numbers adjacent to each symbol.
+    type Model = HashMap<Symbol, Vec<PartNumber>>;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let numbers: HashSet<PartNumber> = self.get_edges().values().flatten().cloned().collect();
-        numbers
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        get_edges(Input::new(DAY, input))
+    }
+
+    fn solve_part_1(edges: &Self::Model) -> Result<Answer, PuzzleError> {
+        let numbers: HashSet<PartNumber> = edges.values().flatten().cloned().collect();
+        Ok(numbers
             .iter()
             .map(|number| number.num)
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 
-    fn solve_part_2(&self) -> String {
-        self.get_edges()
+    fn solve_part_2(edges: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(edges
             .iter()
             .filter_map(|(symbol, numbers)| {
                 if symbol.c == '*' && numbers.len() == 2 {
@@ -28,70 +35,62 @@ impl Puzzle for Day {
             })
             .map(|numbers| numbers.iter().map(|number| number.num).product::<usize>())
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn get_edges(&self) -> HashMap<Symbol, Vec<PartNumber>> {
-        // Discover all part numbers.
-        let mut numbers = Vec::new();
-        for (row, line) in self.input.lines().enumerate() {
-            for cap in regex!(r"\d+").find_iter(line) {
-                let num = cap.as_str().parse::<usize>().unwrap();
-                numbers.push(PartNumber {
-                    num,
-                    row,
-                    start_col: cap.start(),
-                    end_col: cap.end(),
-                });
-            }
+fn get_edges(input: Input) -> Result<HashMap<Symbol, Vec<PartNumber>>, PuzzleError> {
+    // Discover all part numbers.
+    let mut numbers = Vec::new();
+    for (row, line) in input.lines().enumerate() {
+        for cap in regex!(r"\d+").find_iter(line) {
+            let num = input.parse::<usize>(cap.as_str())?;
+            numbers.push(PartNumber {
+                num,
+                row,
+                start_col: cap.start(),
+                end_col: cap.end(),
+            });
         }
+    }
 
-        // Discover all symbols.
-        let mut symbols = Vec::new();
-        for (row, line) in self.input.lines().enumerate() {
-            for (col, c) in line.chars().enumerate() {
-                if c != '.' && !c.is_ascii_digit() {
-                    symbols.push(Symbol { c, row, col });
-                }
+    // Discover all symbols.
+    let mut symbols = Vec::new();
+    for (row, line) in input.lines().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            if c != '.' && !c.is_ascii_digit() {
+                symbols.push(Symbol { c, row, col });
             }
         }
+    }
 
-        // Map which numbers touch which symbols.
-        let mut map = HashMap::new();
-        for symbol in symbols {
-            let mut adjacent_numbers = Vec::new();
-            for number in &numbers {
-                if is_adjacent(&symbol, number) {
-                    adjacent_numbers.push(*number);
-                }
+    // Map which numbers touch which symbols.
+    let mut map = HashMap::new();
+    for symbol in symbols {
+        let mut adjacent_numbers = Vec::new();
+        for number in &numbers {
+            if is_adjacent(&symbol, number) {
+                adjacent_numbers.push(*number);
             }
-            map.insert(symbol, adjacent_numbers);
         }
-        map
+        map.insert(symbol, adjacent_numbers);
     }
+    Ok(map)
 }
 
 #[derive(Eq, PartialEq, Hash, Clone, Copy)]
-struct PartNumber {
-    num: usize,
-    row: usize,
-    start_col: usize,
-    end_col: usize,
+pub struct PartNumber {
+    pub num: usize,
+    pub row: usize,
+    pub start_col: usize,
+    pub end_col: usize,
 }
 
 #[derive(Eq, PartialEq, Hash)]
-struct Symbol {
-    c: char,
-    row: usize,
-    col: usize,
+pub struct Symbol {
+    pub c: char,
+    pub row: usize,
+    pub col: usize,
 }
 
 fn is_adjacent(symbol: &Symbol, number: &PartNumber) -> bool {
@@ -116,15 +115,16 @@ mod tests {
             ......755.\n\
             ...$.*....\n\
            .664.598..";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "4361");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(4361));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/03")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "556367");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/03")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(556367));
     }
 
     #[test]
@@ -139,14 +139,15 @@ mod tests {
             ......755.\n\
             ...$.*....\n\
            .664.598..";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "467835");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(467835));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/03")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "89471771");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/03")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(89471771));
     }
 }
@@ -1,61 +1,70 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex;
 use std::collections::HashSet;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 4;
+
+pub struct Day;
+
+impl Solver for Day {
+    /// The number of winning numbers on each card.
+    type Model = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        let input = Input::new(DAY, input);
+        input.lines().map(|line| parse_card(input, line)).collect()
+    }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.input
-            .lines()
-            .map(parse_card)
-            .map(|n| match n {
+    fn solve_part_1(cards: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(cards
+            .iter()
+            .map(|&n| match n {
                 0 => 0,
                 _ => 1 << (n - 1),
             })
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 
-    fn solve_part_2(&self) -> String {
-        let numbers = self.input.lines().map(parse_card).collect::<Vec<usize>>();
-        let mut counts = vec![1; numbers.len()];
+    fn solve_part_2(cards: &Self::Model) -> Result<Answer, PuzzleError> {
+        let mut counts = vec![1; cards.len()];
         for i in 0..counts.len() {
-            for j in i + 1..=i + numbers[i] {
+            for j in i + 1..=i + cards[i] {
+                if j >= counts.len() {
+                    return Err(PuzzleError::new(
+                        DAY,
+                        format!(
+                            "card {} wins copies of cards past the end of the table",
+                            i + 1
+                        ),
+                    ));
+                }
                 counts[j] += counts[i];
             }
         }
-        counts.iter().sum::<usize>().to_string()
-    }
-}
-
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+        Ok(counts.iter().sum::<usize>().into())
     }
 }
 
-fn parse_card(line: &str) -> usize {
+fn parse_card(input: Input, line: &str) -> Result<usize, PuzzleError> {
     let re = regex!(r".*:(.*)\|(.*)");
-    let cap = re.captures(line).unwrap();
+    let cap = re
+        .captures(line)
+        .ok_or_else(|| input.error(line, "expected 'Card N: winning numbers | numbers'"))?;
 
     let mut winning_numbers = HashSet::new();
-    for m in regex!(r"\d+").find_iter(&cap[1]) {
-        winning_numbers.insert(m.as_str().parse::<usize>().unwrap());
+    for m in regex!(r"\d+").find_iter(cap.get(1).unwrap().as_str()) {
+        winning_numbers.insert(input.parse::<usize>(m.as_str())?);
     }
 
     let mut num_winning_numbers = 0;
-    for m in regex!(r"\d+").find_iter(&cap[2]) {
-        let number = m.as_str().parse::<usize>().unwrap();
+    for m in regex!(r"\d+").find_iter(cap.get(2).unwrap().as_str()) {
+        let number = input.parse::<usize>(m.as_str())?;
         if winning_numbers.contains(&number) {
             num_winning_numbers += 1;
         }
     }
-    num_winning_numbers
+    Ok(num_winning_numbers)
 }
 
 #[cfg(test)]
@@ -71,15 +80,16 @@ mod tests {
             Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\n\
             Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\n\
             Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "13");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(13));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/04")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "33950");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/04")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(33950));
     }
 
     #[test]
@@ -90,14 +100,15 @@ mod tests {
             Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\n\
             Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\n\
             Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "30");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(30));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/04")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "14814534");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/04")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(14814534));
     }
 }
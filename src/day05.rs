@@ -1,95 +1,86 @@
-use crate::puzzle::Puzzle;
+use crate::intervals::{OffsetMap, RangeSet};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex;
-use rangemap::RangeMap;
-use std::ops::Range;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 5;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let mut seeds = Vec::new();
-        let seed_line = self.input.lines().next().unwrap();
-        for m in regex!(r"\d+").find_iter(seed_line) {
-            let seed = m.as_str().parse::<i64>().unwrap();
-            seeds.push(seed..seed + 1);
-        }
-        for map in self.parse_maps() {
-            seeds = map_range(&mut seeds, &map);
-        }
-        seeds
-            .iter()
-            .map(|range| range.start)
-            .min()
-            .unwrap()
-            .to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        let mut seeds = Vec::new();
-        let seed_line = self.input.lines().next().unwrap();
-        for cap in regex!(r"(\d+) (\d+)").captures_iter(seed_line) {
-            let start = cap[1].parse::<i64>().unwrap();
-            let length = cap[2].parse::<i64>().unwrap();
-            seeds.push(start..start + length);
-        }
-        for map in self.parse_maps() {
-            seeds = map_range(&mut seeds, &map);
-        }
-        seeds
-            .iter()
-            .map(|range| range.start)
-            .min()
-            .unwrap()
-            .to_string()
-    }
+/// The seed numbers, and the map that takes them through every step to their locations.
+pub struct Almanac {
+    pub seeds: Vec<i64>,
+    pub seed_to_location: OffsetMap,
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
+impl Solver for Day {
+    type Model = Almanac;
+
+    fn parse(input: &str) -> Result<Almanac, PuzzleError> {
+        let input = Input::new(DAY, input);
+        let seed_line = input
+            .lines()
+            .next()
+            .ok_or_else(|| PuzzleError::new(DAY, "input is empty"))?;
+        let seeds = regex!(r"\d+")
+            .find_iter(seed_line)
+            .map(|m| input.parse::<i64>(m.as_str()))
+            .collect::<Result<Vec<i64>, PuzzleError>>()?;
+        Ok(Almanac {
+            seeds,
+            seed_to_location: parse_maps(input)?
+                .iter()
+                .fold(OffsetMap::new(), |composed, map| composed.then(map)),
         })
     }
 
-    fn parse_maps(&self) -> Vec<RangeMap<i64, i64>> {
-        let mut maps = Vec::new();
-        for block in self.input.split("\n\n").skip(1) {
-            let mut map = RangeMap::new();
-            for line in block.lines().skip(1) {
-                let mut parts = line.split_whitespace();
-                let dst = parts.next().unwrap().parse::<i64>().unwrap();
-                let src = parts.next().unwrap().parse::<i64>().unwrap();
-                let length = parts.next().unwrap().parse::<i64>().unwrap();
-                map.insert(src..src + length, dst - src);
-            }
-            maps.push(map);
+    fn solve_part_1(almanac: &Almanac) -> Result<Answer, PuzzleError> {
+        let seeds = almanac.seeds.iter().map(|&seed| seed..seed + 1).collect();
+        almanac.lowest_location(seeds)
+    }
+
+    fn solve_part_2(almanac: &Almanac) -> Result<Answer, PuzzleError> {
+        if almanac.seeds.len() % 2 == 1 {
+            return Err(PuzzleError::new(
+                DAY,
+                "seeds must come in pairs of start and length",
+            ));
         }
-        maps
+        let seeds = almanac
+            .seeds
+            .chunks(2)
+            .map(|pair| pair[0]..pair[0] + pair[1])
+            .collect();
+        almanac.lowest_location(seeds)
     }
 }
 
-fn map_range(inputs: &mut Vec<Range<i64>>, map: &RangeMap<i64, i64>) -> Vec<Range<i64>> {
-    let mut output = Vec::new();
-    while let Some(input) = inputs.pop() {
-        if map.overlaps(&input) {
-            for (range, offset) in map.overlapping(&input) {
-                let start = std::cmp::max(input.start, range.start);
-                let end = std::cmp::min(input.end, range.end);
-                output.push(start + offset..end + offset);
-                if input.start < start {
-                    inputs.push(input.start..start);
-                }
-                if end < input.end {
-                    inputs.push(end..input.end);
-                }
-            }
-        } else {
-            output.push(input);
+impl Almanac {
+    pub fn lowest_location(&self, seeds: RangeSet) -> Result<Answer, PuzzleError> {
+        self.seed_to_location
+            .apply(&seeds)
+            .min()
+            .map(Answer::from)
+            .ok_or_else(|| PuzzleError::new(DAY, "no seeds"))
+    }
+}
+
+fn parse_maps(input: Input) -> Result<Vec<OffsetMap>, PuzzleError> {
+    let mut maps = Vec::new();
+    for block in input.text.split("\n\n").skip(1) {
+        let mut map = OffsetMap::new();
+        for line in block.lines().skip(1) {
+            let numbers = line
+                .split_whitespace()
+                .map(|part| input.parse::<i64>(part))
+                .collect::<Result<Vec<i64>, PuzzleError>>()?;
+            let [dst, src, length] = numbers[..] else {
+                return Err(input.error(line, "expected 'destination source length'"));
+            };
+            map.insert(src..src + length, dst - src);
         }
+        maps.push(map);
     }
-    output
+    Ok(maps)
 }
 
 #[cfg(test)]
@@ -132,15 +123,16 @@ mod tests {
             humidity-to-location map:\n\
             60 56 37\n\
             56 93 4";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "35");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(35));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/05")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "1181555926");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/05")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(1181555926));
     }
 
     #[test]
@@ -178,14 +170,28 @@ mod tests {
             humidity-to-location map:\n\
             60 56 37\n\
             56 93 4";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "46");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(46));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/05")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "37806486");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/05")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(37806486));
+    }
+
+    #[test]
+    fn test_invalid_map_entry() {
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 5x 48";
+        let location = Day::parse(input).err().unwrap().location.unwrap();
+        assert_eq!((location.line, location.column), (5, 4));
+        assert_eq!(location.text, "5x");
+        let error = Day::parse("seeds: 79 14\n\nmap:\n50 98").err().unwrap();
+        assert_eq!(error.location.unwrap().text, "50 98");
+        let almanac = Day::parse("seeds: 79 14 55\n\nmap:\n50 98 2").unwrap();
+        assert!(Day::solve_part_1(&almanac).is_ok());
+        assert!(Day::solve_part_2(&almanac).is_err());
     }
 }
@@ -1,57 +1,121 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, Location, PuzzleError, Solver};
 
-pub struct Day {
-    input: String,
+const DAY: u8 = 6;
+
+pub struct Day;
+
+/// The races on the sheet, and the single race read by ignoring the spaces between numbers.
+pub struct Races {
+    pub races: Vec<(u64, u64)>,
+    pub combined: (u64, u64),
+    /// Where the time and record of each race are in the input, to report numbers too large to
+    /// solve with.
+    pub locations: Vec<(Option<Location>, Option<Location>)>,
+    pub combined_locations: (Option<Location>, Option<Location>),
 }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let mut lines = self.input.lines();
-        let times = parse_line_to_numbers(lines.next().unwrap());
-        let distances = parse_line_to_numbers(lines.next().unwrap());
-        let mut result = 1;
-        for (time, record) in times.iter().zip(distances) {
-            result *= calculate_ways_to_win(*time, record);
+impl Solver for Day {
+    type Model = Races;
+
+    fn parse(text: &str) -> Result<Races, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut lines = input.lines();
+        let (time_line, distance_line) = lines.next().zip(lines.next()).ok_or_else(|| {
+            PuzzleError::new(DAY, "expected a line of times and one of distances")
+        })?;
+        let times = parse_line_to_numbers(input, time_line)?;
+        let distances = parse_line_to_numbers(input, distance_line)?;
+        if times.len() != distances.len() {
+            return Err(input.error(
+                distance_line,
+                format!("expected {} distances, one for each time", times.len()),
+            ));
         }
-        result.to_string()
+        let (races, locations) = times
+            .into_iter()
+            .zip(distances)
+            .map(|((time_text, time), (record_text, record))| {
+                (
+                    (time, record),
+                    (input.location(time_text), input.location(record_text)),
+                )
+            })
+            .unzip();
+        Ok(Races {
+            races,
+            combined: (
+                extract_single_number_from_line(input, time_line)?,
+                extract_single_number_from_line(input, distance_line)?,
+            ),
+            locations,
+            combined_locations: (input.location(time_line), input.location(distance_line)),
+        })
     }
 
-    fn solve_part_2(&self) -> String {
-        let mut lines = self.input.lines();
-        let time = extract_single_number_from_line(lines.next().unwrap());
-        let distance = extract_single_number_from_line(lines.next().unwrap());
-        calculate_ways_to_win(time, distance).to_string()
+    fn solve_part_1(races: &Races) -> Result<Answer, PuzzleError> {
+        let mut result = 1u64;
+        for (&race, locations) in races.races.iter().zip(&races.locations) {
+            result = result
+                .checked_mul(ways_to_win(race, locations)?)
+                .ok_or_else(|| {
+                    PuzzleError::new(DAY, "the product of the ways to win is too large")
+                })?;
+        }
+        Ok(result.into())
     }
-}
 
-fn calculate_ways_to_win(time: u64, distance: u64) -> u64 {
-    let discriminant = (time * time - 4 * distance) as f64;
-    let sqrt_discriminant = discriminant.sqrt();
-    let min_hold_time = (((time as f64) - sqrt_discriminant) / 2.0).floor() as u64;
-    let max_hold_time = (((time as f64) + sqrt_discriminant) / 2.0).ceil() as u64;
-    max_hold_time - min_hold_time - 1
+    fn solve_part_2(races: &Races) -> Result<Answer, PuzzleError> {
+        Ok(ways_to_win(races.combined, &races.combined_locations)?.into())
+    }
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+/// Returns how many whole numbers of milliseconds the button can be held for to go further than
+/// the record in a race, which is none if the record cannot be beaten. Fails, at the given
+/// locations of the time and record, if they are too large to solve with.
+fn ways_to_win(
+    (time, record): (u64, u64),
+    (time_location, record_location): &(Option<Location>, Option<Location>),
+) -> Result<u64, PuzzleError> {
+    let too_large = |location: &Option<Location>, number: &str| PuzzleError {
+        day: DAY,
+        location: location.clone(),
+        message: format!("the {} is too large", number),
+    };
+    // Holding for h goes h * (time - h), which beats the record between the roots of
+    // h^2 - time * h + record = 0.
+    let square = time
+        .checked_mul(time)
+        .ok_or_else(|| too_large(time_location, "time"))?;
+    let four_records = record
+        .checked_mul(4)
+        .ok_or_else(|| too_large(record_location, "record"))?;
+    if square <= four_records {
+        return Ok(0);
     }
+    let sqrt_discriminant = ((square - four_records) as f64).sqrt();
+    let min_hold_time = (((time as f64) - sqrt_discriminant) / 2.0).floor() as u64;
+    let max_hold_time = (((time as f64) + sqrt_discriminant) / 2.0).ceil() as u64;
+    Ok(max_hold_time - min_hold_time - 1)
 }
 
-fn parse_line_to_numbers(line: &str) -> Vec<u64> {
-    line.split_whitespace()
-        .filter_map(|s| s.parse::<u64>().ok())
-        .collect::<Vec<u64>>()
+/// Returns the numbers after the label of a line, with the text of each.
+fn parse_line_to_numbers<'a>(
+    input: Input,
+    line: &'a str,
+) -> Result<Vec<(&'a str, u64)>, PuzzleError> {
+    let numbers = line.split_once(':').map_or(line, |(_, numbers)| numbers);
+    numbers
+        .split_whitespace()
+        .map(|text| Ok((text, input.parse::<u64>(text)?)))
+        .collect()
 }
 
-fn extract_single_number_from_line(line: &str) -> u64 {
+fn extract_single_number_from_line(input: Input, line: &str) -> Result<u64, PuzzleError> {
     line.chars()
         .filter(char::is_ascii_digit)
         .collect::<String>()
         .parse::<u64>()
-        .unwrap()
+        .map_err(|e| input.error(line, format!("expected a number split by spaces: {}", e)))
 }
 
 #[cfg(test)]
@@ -62,28 +126,55 @@ mod tests {
     #[test]
     fn test_part_1_example_1() {
         let input = "Time:      7  15   30\nDistance:  9  40  200";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "288");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(288));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/06")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "275724");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/06")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(275724));
     }
 
     #[test]
     fn test_part_2_example_1() {
         let input = "Time:      7  15   30\nDistance:  9  40  200";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "71503");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(71503));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/06")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "37286485");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/06")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(37286485));
+    }
+
+    #[test]
+    fn test_invalid_races() {
+        let error = Day::parse("Time: 7 1x\nDistance: 9 40").err().unwrap();
+        assert_eq!(error.location.unwrap().text, "1x");
+        let error = Day::parse("Time: 7 15 30\nDistance: 9 40").err().unwrap();
+        assert_eq!(error.location.unwrap().line, 2);
+        // A time too large for part 1 is too large for part 2 as well, but not the other way round.
+        let puzzle = Day::create("Time: 5000000000 1\nDistance: 9 0").unwrap();
+        let error = puzzle.solve_part_1().unwrap_err();
+        assert_eq!(error.location.unwrap().text, "5000000000");
+        let puzzle = Day::create("Time: 500000 0000\nDistance: 9 0").unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(0));
+        let location = puzzle.solve_part_2().unwrap_err().location.unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+    }
+
+    #[test]
+    fn test_unbeatable_records() {
+        // Holding for 1 or 2 milliseconds of 3 goes 2 at best, which only equals the record.
+        let puzzle = Day::create("Time: 3 4 7\nDistance: 2 9 9").unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(0));
+        let puzzle = Day::create("Time: 7\nDistance: 9").unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(4));
     }
 }
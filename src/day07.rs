@@ -1,61 +1,62 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
-pub struct Day {
-    input: String,
-}
-
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.solve_generic(compare_hands)
-    }
+const DAY: u8 = 7;
 
-    fn solve_part_2(&self) -> String {
-        self.solve_generic(compare_hands_with_wildcards)
-    }
-}
+pub struct Day;
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
+impl Solver for Day {
+    type Model = Vec<Hand>;
 
-    fn parse_hands(&self) -> Vec<Hand> {
-        self.input
+    fn parse(text: &str) -> Result<Vec<Hand>, PuzzleError> {
+        let input = Input::new(DAY, text);
+        input
             .lines()
             .map(|line| {
                 let mut parts = line.split_whitespace();
-                let cards = parts.next().unwrap();
-                let bid = parts.next().unwrap().parse::<i32>().unwrap();
-                Hand {
-                    cards: cards.to_string(),
-                    bid,
+                let (Some(cards), Some(bid)) = (parts.next(), parts.next()) else {
+                    return Err(input.error(line, "expected 'cards bid'"));
+                };
+                if let Some((i, c)) = cards
+                    .char_indices()
+                    .find(|&(_, c)| !"23456789TJQKA".contains(c))
+                {
+                    return Err(input.error(&cards[i..i + c.len_utf8()], "invalid card"));
                 }
+                Ok(Hand {
+                    cards: cards.to_string(),
+                    bid: input.parse::<i32>(bid)?,
+                })
             })
             .collect()
     }
 
-    fn solve_generic<F>(&self, compare: F) -> String
-    where
-        F: Fn(&str, &str) -> Ordering,
-    {
-        let mut hands = self.parse_hands();
-        hands.sort_unstable_by(|a, b| compare(&a.cards, &b.cards));
-        hands
-            .into_iter()
-            .enumerate()
-            .map(|(i, hand)| hand.bid * (i as i32 + 1))
-            .sum::<i32>()
-            .to_string()
+    fn solve_part_1(hands: &Vec<Hand>) -> Result<Answer, PuzzleError> {
+        Ok(total_winnings(hands, compare_hands).into())
     }
+
+    fn solve_part_2(hands: &Vec<Hand>) -> Result<Answer, PuzzleError> {
+        Ok(total_winnings(hands, compare_hands_with_wildcards).into())
+    }
+}
+
+pub fn total_winnings<F>(hands: &[Hand], compare: F) -> i32
+where
+    F: Fn(&str, &str) -> Ordering,
+{
+    let mut hands: Vec<&Hand> = hands.iter().collect();
+    hands.sort_unstable_by(|a, b| compare(&a.cards, &b.cards));
+    hands
+        .into_iter()
+        .enumerate()
+        .map(|(i, hand)| hand.bid * (i as i32 + 1))
+        .sum()
 }
 
-struct Hand {
-    cards: String,
-    bid: i32,
+pub struct Hand {
+    pub cards: String,
+    pub bid: i32,
 }
 
 fn card_counts(cards: &str) -> Vec<(char, u8)> {
@@ -94,7 +95,7 @@ fn hand_strength_with_wildcards(cards: &str) -> u8 {
     hand_strength(&replaced)
 }
 
-fn compare_hands(cards_1: &str, cards_2: &str) -> Ordering {
+pub fn compare_hands(cards_1: &str, cards_2: &str) -> Ordering {
     let strength_1 = hand_strength(cards_1);
     let strength_2 = hand_strength(cards_2);
     if strength_1 != strength_2 {
@@ -110,7 +111,7 @@ fn compare_hands(cards_1: &str, cards_2: &str) -> Ordering {
     Ordering::Equal
 }
 
-fn compare_hands_with_wildcards(cards_1: &str, cards_2: &str) -> Ordering {
+pub fn compare_hands_with_wildcards(cards_1: &str, cards_2: &str) -> Ordering {
     let strength_1 = hand_strength_with_wildcards(cards_1);
     let strength_2 = hand_strength_with_wildcards(cards_2);
     if strength_1 != strength_2 {
@@ -138,15 +139,16 @@ mod tests {
             KK677 28\n\
             KTJJT 220\n\
             QQQJA 483";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "6440");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(6440));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/07")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "251106089");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/07")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(251106089));
     }
 
     #[test]
@@ -156,14 +158,22 @@ mod tests {
             KK677 28\n\
             KTJJT 220\n\
             QQQJA 483";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "5905");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(5905));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/07")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "249620106");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/07")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(249620106));
+    }
+
+    #[test]
+    fn test_invalid_card() {
+        let location = Day::parse("32T3é 765").err().unwrap().location.unwrap();
+        assert_eq!((location.line, location.column), (1, 5));
+        assert_eq!(location.text, "é");
     }
 }
@@ -1,78 +1,98 @@
-use crate::puzzle::Puzzle;
+use crate::cycles::{align, Periodic};
+use crate::graph::Graph;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex_captures;
-use std::collections::HashMap;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 8;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.parse_network()
-            .num_steps("AAA", |node| node == "ZZZ")
-            .to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        let network = self.parse_network();
-        network
-            .graph
-            .keys()
-            .filter(|key| key.ends_with('A'))
-            .map(|key| network.num_steps(key, |node| node.ends_with('Z')))
-            .fold(1, num::integer::lcm)
-            .to_string()
-    }
-}
+impl Solver for Day {
+    type Model = Network;
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
+    fn parse(text: &str) -> Result<Network, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut sections = text.split("\n\n");
+        let (Some(instructions), Some(nodes)) = (sections.next(), sections.next()) else {
+            return Err(PuzzleError::new(
+                DAY,
+                "expected instructions and nodes separated by a blank line",
+            ));
+        };
+        if instructions.is_empty() {
+            return Err(PuzzleError::new(DAY, "no instructions"));
+        }
+        if let Some((i, c)) = instructions
+            .char_indices()
+            .find(|&(_, c)| c != 'L' && c != 'R')
+        {
+            return Err(input.error(&instructions[i..i + c.len_utf8()], "invalid instruction"));
+        }
+        let mut graph = Graph::new();
+        for line in nodes.lines() {
+            let (_, node, left, right) = regex_captures!(r"(\S+) = \((\S+), (\S+)\)", line)
+                .ok_or_else(|| input.error(line, "expected 'NODE = (LEFT, RIGHT)'"))?;
+            let (node, left, right) = (graph.node(node), graph.node(left), graph.node(right));
+            graph.add_edge(node, left, 'L');
+            graph.add_edge(node, right, 'R');
+        }
+        Ok(Network {
+            instructions: instructions.to_string(),
+            graph,
         })
     }
 
-    fn parse_network(&self) -> Network {
-        let mut sections = self.input.split("\n\n");
-        let instructions = sections.next().unwrap().to_string();
-        let mut graph = HashMap::new();
-        for line in sections.next().unwrap().lines() {
-            let (_, node, left, right) =
-                regex_captures!(r"(\S+) = \((\S+), (\S+)\)", line).unwrap();
-            graph.insert(node.to_string(), (left.to_string(), right.to_string()));
-        }
-        Network {
-            instructions,
-            graph,
-        }
+    fn solve_part_1(network: &Network) -> Result<Answer, PuzzleError> {
+        Ok(network.num_steps("AAA", |node| node == "ZZZ")?.into())
+    }
+
+    fn solve_part_2(network: &Network) -> Result<Answer, PuzzleError> {
+        // Each ghost reaches an end node after some number of steps and, as the puzzle is built,
+        // again after every multiple of that number.
+        let ghosts = network
+            .graph
+            .nodes()
+            .map(|node| network.graph.key(node))
+            .filter(|key| key.ends_with('A'))
+            .map(|key| {
+                let steps = network.num_steps(key, |node| node.ends_with('Z'))? as u64;
+                Ok(Periodic::new(steps, steps))
+            })
+            .collect::<Result<Vec<Periodic>, PuzzleError>>()?;
+        let together = align(&ghosts).ok_or_else(|| {
+            PuzzleError::new(DAY, "the ghosts never all reach end nodes together")
+        })?;
+        Ok(together.offset.into())
     }
 }
 
-struct Network {
-    instructions: String,
-    graph: HashMap<String, (String, String)>,
+pub struct Network {
+    pub instructions: String,
+    /// The nodes, with an edge labelled 'L' or 'R' to each of the two nodes they lead to.
+    pub graph: Graph<String, char>,
 }
 
 impl Network {
-    fn num_steps<F>(&self, start: &str, is_end: F) -> usize
+    pub fn num_steps<F>(&self, start: &str, is_end: F) -> Result<usize, PuzzleError>
     where
         F: Fn(&str) -> bool,
     {
-        let mut node = start;
+        let missing = |node: &str| PuzzleError::new(DAY, format!("no node named '{}'", node));
+        let mut node = self.graph.id(start).ok_or_else(|| missing(start))?;
         let mut depth = 0;
         for instruction in self.instructions.chars().cycle() {
-            let (left, right) = self.graph.get(node).unwrap();
-            node = match instruction {
-                'L' => left,
-                'R' => right,
-                _ => unreachable!(),
-            };
+            node = self
+                .graph
+                .successors(node)
+                .find(|(_, &label)| label == instruction)
+                .map(|(next, _)| next)
+                .ok_or_else(|| missing(self.graph.key(node)))?;
             depth += 1;
-            if is_end(node) {
+            if is_end(self.graph.key(node)) {
                 break;
             }
         }
-        depth
+        Ok(depth)
     }
 }
 
@@ -92,8 +112,8 @@ mod tests {
             EEE = (EEE, EEE)\n\
             GGG = (GGG, GGG)\n\
             ZZZ = (ZZZ, ZZZ)";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "2");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(2));
     }
 
     #[test]
@@ -103,15 +123,16 @@ mod tests {
             AAA = (BBB, BBB)\n\
             BBB = (AAA, ZZZ)\n\
             ZZZ = (ZZZ, ZZZ)";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "6");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(6));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/08")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "14257");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/08")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(14257));
     }
 
     #[test]
@@ -126,14 +147,26 @@ mod tests {
             22C = (22Z, 22Z)\n\
             22Z = (22B, 22B)\n\
             XXX = (XXX, XXX)";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "6");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(6));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/08")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "16187743689077");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/08")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(16187743689077));
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        let location = Day::parse("LRé\n\nAAA = (AAA, AAA)")
+            .err()
+            .unwrap()
+            .location
+            .unwrap();
+        assert_eq!((location.line, location.column), (1, 3));
+        assert_eq!(location.text, "é");
     }
 }
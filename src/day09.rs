@@ -1,45 +1,42 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use num::integer::binomial;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 9;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.input
-            .lines()
-            .map(parse_line)
-            .map(successor)
-            .sum::<i64>()
-            .to_string()
+pub struct Day;
+
+impl Solver for Day {
+    type Model = Vec<Vec<i64>>;
+
+    fn parse(text: &str) -> Result<Vec<Vec<i64>>, PuzzleError> {
+        let input = Input::new(DAY, text);
+        input.lines().map(|line| parse_line(input, line)).collect()
     }
 
-    fn solve_part_2(&self) -> String {
-        self.input
-            .lines()
-            .map(parse_line)
-            .map(predecessor)
+    fn solve_part_1(histories: &Vec<Vec<i64>>) -> Result<Answer, PuzzleError> {
+        Ok(histories
+            .iter()
+            .map(|nums| successor(nums))
             .sum::<i64>()
-            .to_string()
+            .into())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_2(histories: &Vec<Vec<i64>>) -> Result<Answer, PuzzleError> {
+        Ok(histories
+            .iter()
+            .map(|nums| predecessor(nums))
+            .sum::<i64>()
+            .into())
     }
 }
 
-fn parse_line(line: &str) -> Vec<i64> {
+fn parse_line(input: Input, line: &str) -> Result<Vec<i64>, PuzzleError> {
     line.split_whitespace()
-        .map(|x| x.parse::<i64>().unwrap())
+        .map(|x| input.parse::<i64>(x))
         .collect()
 }
 
-fn successor(nums: Vec<i64>) -> i64 {
+pub fn successor(nums: &[i64]) -> i64 {
     nums.iter()
         .enumerate()
         .map(|(i, &x)| {
@@ -49,7 +46,7 @@ fn successor(nums: Vec<i64>) -> i64 {
         .sum()
 }
 
-fn predecessor(nums: Vec<i64>) -> i64 {
+pub fn predecessor(nums: &[i64]) -> i64 {
     nums.iter()
         .enumerate()
         .map(|(i, &x)| {
@@ -69,15 +66,16 @@ mod tests {
         let input = "0 3 6 9 12 15\n\
             1 3 6 10 15 21\n\
             10 13 16 21 30 45";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "114");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(114));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/09")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "1684566095");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/09")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(1684566095));
     }
 
     #[test]
@@ -85,14 +83,15 @@ mod tests {
         let input = "0 3 6 9 12 15\n\
             1 3 6 10 15 21\n\
             10 13 16 21 30 45";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "2");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(2));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/09")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "1136");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/09")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(1136));
     }
 }
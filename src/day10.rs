@@ -1,152 +1,114 @@
-use crate::puzzle::Puzzle;
+use crate::geometry::Polygon;
+use crate::grid::{Direction, Grid, Point};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 
-pub struct Day {
-    input: String,
-}
-
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let pipes = Pipes::parse(&self.input);
-        let boundary = pipes.find_starting_point();
-        let mut direction = pipes.find_initial_direction(boundary);
-        let mut position = boundary + direction;
-        let mut steps = 1;
-        while pipes[position] != 'S' {
-            direction = next_direction(pipes[position], direction);
-            position += direction;
-            steps += 1;
-        }
-        (steps / 2).to_string()
-    }
-
-    fn solve_part_2(&self) -> String {
-        let pipes = Pipes::parse(&self.input);
-        let mut boundary = pipes.find_starting_point();
-        let mut direction = pipes.find_initial_direction(boundary);
-        let mut position = boundary + direction;
-        let mut steps = 1;
-        let mut area = 0;
-        while pipes[position] != 'S' {
-            if matches!(pipes[position], 'L' | 'J' | '7' | 'F') {
-                area += boundary.row * position.col - boundary.col * position.row;
-                boundary = position;
-            }
-            direction = next_direction(pipes[position], direction);
-            position += direction;
-            steps += 1;
-        }
-        area += boundary.row * position.col - boundary.col * position.row;
-        (area.abs() / 2 - steps / 2 + 1).to_string()
-    }
-}
+const DAY: u8 = 10;
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-}
+pub struct Day;
 
-struct Pipes {
-    grid: Vec<Vec<char>>,
-}
+impl Solver for Day {
+    /// The loop of pipes through the starting point.
+    type Model = Polygon;
 
-impl Pipes {
-    fn parse(input: &str) -> Pipes {
-        Pipes {
-            grid: input.lines().map(|line| line.chars().collect()).collect(),
-        }
+    fn parse(text: &str) -> Result<Polygon, PuzzleError> {
+        let pipes = Pipes::parse(Input::new(DAY, text))?;
+        let start = pipes.find_starting_point()?;
+        let steps = pipes.walk_loop(start)?;
+        Polygon::from_steps(steps.into_iter().map(|direction| (direction, 1)))
+            .map_err(|e| PuzzleError::new(DAY, e))
     }
 
-    fn num_rows(&self) -> i32 {
-        self.grid.len() as i32
+    fn solve_part_1(pipe_loop: &Polygon) -> Result<Answer, PuzzleError> {
+        Ok((pipe_loop.boundary_points() / 2).into())
     }
 
-    fn num_cols(&self) -> i32 {
-        self.grid[0].len() as i32
-    }
-
-    fn find_starting_point(&self) -> Point {
-        self.grid
-            .iter()
-            .enumerate()
-            .find_map(|(row, line)| {
-                line.iter().enumerate().find_map(|(col, &ch)| {
-                    if ch == 'S' {
-                        Some(Point {
-                            row: row as i32,
-                            col: col as i32,
-                        })
-                    } else {
-                        None
-                    }
-                })
-            })
-            .unwrap()
-    }
-
-    fn find_initial_direction(&self, start: Point) -> Point {
-        if start.row > 0 && matches!(self[start + NORTH], '|' | '7' | 'F') {
-            NORTH
-        } else if start.row < self.num_rows() - 1 && matches!(self[start + SOUTH], '|' | 'L' | 'J')
-        {
-            SOUTH
-        } else if start.col < self.num_cols() - 1 && matches!(self[start + EAST], '-' | 'L' | 'F') {
-            EAST
-        } else if start.col > 0 && matches!(self[start + WEST], '-' | 'J' | '7') {
-            WEST
-        } else {
-            unreachable!();
-        }
+    fn solve_part_2(pipe_loop: &Polygon) -> Result<Answer, PuzzleError> {
+        Ok(pipe_loop.interior_points().into())
     }
 }
 
-impl std::ops::Index<Point> for Pipes {
-    type Output = char;
-    fn index(&self, index: Point) -> &Self::Output {
-        &self.grid[index.row as usize][index.col as usize]
-    }
+struct Pipes<'a> {
+    input: Input<'a>,
+    grid: Grid<char>,
 }
 
-const NORTH: Point = Point { row: -1, col: 0 };
-const SOUTH: Point = Point { row: 1, col: 0 };
-const EAST: Point = Point { row: 0, col: 1 };
-const WEST: Point = Point { row: 0, col: -1 };
-
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
-struct Point {
-    row: i32,
-    col: i32,
-}
+impl<'a> Pipes<'a> {
+    fn parse(input: Input<'a>) -> Result<Pipes<'a>, PuzzleError> {
+        Ok(Pipes {
+            input,
+            grid: Grid::parse(input, "|-LJ7F.S")?,
+        })
+    }
 
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, other: Point) -> Point {
-        Point {
-            row: self.row + other.row,
-            col: self.col + other.col,
+    fn find_starting_point(&self) -> Result<Point, PuzzleError> {
+        self.grid
+            .find(&'S')
+            .ok_or_else(|| PuzzleError::new(DAY, "no starting point 'S'"))
+    }
+
+    fn find_initial_direction(&self, start: Point) -> Result<Direction, PuzzleError> {
+        [
+            (Direction::North, "|7F"),
+            (Direction::South, "|LJ"),
+            (Direction::East, "-LF"),
+            (Direction::West, "-J7"),
+        ]
+        .into_iter()
+        .find(|(direction, pipes)| {
+            self.grid
+                .get(start.step(*direction))
+                .is_some_and(|tile| pipes.contains(*tile))
+        })
+        .map(|(direction, _)| direction)
+        .ok_or_else(|| self.error_at(start, "no pipe connects to the starting point"))
+    }
+
+    /// Follows the loop from the starting point back to itself, returning the direction of every
+    /// step along the way.
+    fn walk_loop(&self, start: Point) -> Result<Vec<Direction>, PuzzleError> {
+        let mut direction = self.find_initial_direction(start)?;
+        let mut position = start.step(direction);
+        let mut steps = vec![direction];
+        loop {
+            let tile = *self
+                .grid
+                .get(position)
+                .ok_or_else(|| PuzzleError::new(DAY, "the loop leaves the grid"))?;
+            if tile == 'S' {
+                return Ok(steps);
+            }
+            direction = next_direction(tile, direction)
+                .ok_or_else(|| self.error_at(position, "pipe does not continue the loop"))?;
+            position = position.step(direction);
+            steps.push(direction);
         }
     }
-}
 
-impl std::ops::AddAssign for Point {
-    fn add_assign(&mut self, other: Point) {
-        *self = *self + other;
+    fn error_at(&self, position: Point, message: &str) -> PuzzleError {
+        let line = self
+            .input
+            .lines()
+            .nth(position.row as usize)
+            .unwrap_or_default();
+        let at = line
+            .char_indices()
+            .nth(position.col as usize)
+            .map_or(line, |(i, c)| &line[i..i + c.len_utf8()]);
+        self.input.error(at, message)
     }
 }
 
-fn next_direction(tile: char, direction: Point) -> Point {
-    match tile {
-        'L' if direction == SOUTH => EAST,
-        'L' if direction == WEST => NORTH,
-        'J' if direction == SOUTH => WEST,
-        'J' if direction == EAST => NORTH,
-        '7' if direction == NORTH => WEST,
-        '7' if direction == EAST => SOUTH,
-        'F' if direction == NORTH => EAST,
-        'F' if direction == WEST => SOUTH,
-        _ => direction,
+/// Returns the direction to leave a pipe in after entering it in the given direction, if the pipe
+/// connects to the tile it was entered from.
+fn next_direction(tile: char, direction: Direction) -> Option<Direction> {
+    use Direction::{East, North, South, West};
+    match (tile, direction) {
+        ('|', North | South) | ('-', East | West) => Some(direction),
+        ('L', South) | ('F', North) => Some(East),
+        ('L', West) | ('J', East) => Some(North),
+        ('J', South) | ('7', North) => Some(West),
+        ('7', East) | ('F', West) => Some(South),
+        _ => None,
     }
 }
 
@@ -162,8 +124,8 @@ mod tests {
             .|.|.\n\
             .L-J.\n\
             .....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "4");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(4));
     }
 
     #[test]
@@ -173,8 +135,8 @@ mod tests {
             SJ.L7\n\
             |F--J\n\
             LJ...";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "8");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(8));
     }
 
     #[test]
@@ -184,15 +146,16 @@ mod tests {
             SJLL7\n\
             |F--J\n\
             LJ.LJ";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "8");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(8));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/10")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "6733");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/10")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(6733));
     }
 
     #[test]
@@ -206,8 +169,8 @@ mod tests {
             .|..|.|..|.\n\
             .L--J.L--J.\n\
             ...........";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "4");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(4));
     }
 
     #[test]
@@ -221,8 +184,8 @@ mod tests {
             .|..||..|.\n\
             .L--JL--J.\n\
             ..........";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "4");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(4));
     }
 
     #[test]
@@ -237,8 +200,8 @@ mod tests {
             .....|FJLJ|FJ|F7|.LJ\n\
             ....FJL-7.||.||||...\n\
             ....L---J.LJ.LJLJ...";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "8");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(8));
     }
 
     #[test]
@@ -253,14 +216,30 @@ mod tests {
             7-L-JL7||F7|L7F-7F7|\n\
             L.L7LFJ|||||FJL7||LJ\n\
             L7JLJL-JLJLJL--JLJ.L";
-        let puzzle = Day::create(input);
-        assert_eq!(puzzle.solve_part_2(), "10");
+        let puzzle = Day::create(input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(10));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/10")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "435");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/10")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(435));
+    }
+
+    #[test]
+    fn test_broken_loop() {
+        let input = "\
+            .....\n\
+            .S-7.\n\
+            .|.|.\n\
+            .L-..\n\
+            .....";
+        let error = Day::parse(input).err().unwrap();
+        let location = error.location.unwrap();
+        assert_eq!((location.line, location.column), (4, 4));
+        assert_eq!(location.text, ".");
+        assert!(Day::create("...").is_err());
     }
 }
@@ -1,43 +1,51 @@
-use crate::puzzle::Puzzle;
-use num::integer::Roots;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 
-pub struct Day {
-    input: String,
+const DAY: u8 = 11;
+
+pub struct Day;
+
+/// The number of galaxies in each column and each row of the image.
+pub struct GalaxyCounts {
+    pub x: Vec<usize>,
+    pub y: Vec<usize>,
 }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        solve_generic(&self.input, 2).to_string()
+impl Solver for Day {
+    type Model = GalaxyCounts;
+
+    fn parse(text: &str) -> Result<GalaxyCounts, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let width = input.lines().map(|line| line.chars().count()).max();
+        let (mut x, mut y) = (vec![0; width.unwrap_or(0)], vec![0; input.lines().count()]);
+        for (row, line) in input.lines().enumerate() {
+            for (col, (i, ch)) in line.char_indices().enumerate() {
+                match ch {
+                    '#' => {
+                        x[col] += 1;
+                        y[row] += 1;
+                    }
+                    '.' => {}
+                    _ => return Err(input.error(&line[i..i + ch.len_utf8()], "invalid tile")),
+                }
+            }
+        }
+        Ok(GalaxyCounts { x, y })
     }
 
-    fn solve_part_2(&self) -> String {
-        solve_generic(&self.input, 1_000_000).to_string()
+    fn solve_part_1(counts: &GalaxyCounts) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(counts, 2).into())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_2(counts: &GalaxyCounts) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(counts, 1_000_000).into())
     }
 }
 
-fn solve_generic(input: &str, gap_factor: usize) -> usize {
-    let size = input.len().sqrt();
-    let (mut x_counts, mut y_counts) = (vec![0; size], vec![0; size]);
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            if ch == '#' {
-                x_counts[x] += 1;
-                y_counts[y] += 1;
-            }
-        }
-    }
-    dist(x_counts, gap_factor) + dist(y_counts, gap_factor)
+pub fn solve_generic(counts: &GalaxyCounts, gap_factor: usize) -> usize {
+    dist(&counts.x, gap_factor) + dist(&counts.y, gap_factor)
 }
 
-fn dist(galaxy_counts: Vec<usize>, gap_factor: usize) -> usize {
+fn dist(galaxy_counts: &[usize], gap_factor: usize) -> usize {
     let mut gap_count = 0;
     let mut galaxies_seen = 0;
     let mut total_distance = 0;
@@ -72,15 +80,16 @@ mod tests {
             ..........\n\
             .......#..\n\
             #...#.....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "374");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(374));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/11")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "9957702");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/11")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(9957702));
     }
 
     #[test]
@@ -95,7 +104,7 @@ mod tests {
             ..........\n\
             .......#..\n\
             #...#.....";
-        assert_eq!(solve_generic(input, 10), 1030);
+        assert_eq!(solve_generic(&Day::parse(input).unwrap(), 10), 1030);
     }
 
     #[test]
@@ -110,13 +119,14 @@ mod tests {
             ..........\n\
             .......#..\n\
             #...#.....";
-        assert_eq!(solve_generic(input, 100), 8410);
+        assert_eq!(solve_generic(&Day::parse(input).unwrap(), 100), 8410);
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/11")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "512240933238");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/11")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(512240933238));
     }
 }
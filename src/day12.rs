@@ -1,61 +1,69 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use rayon::prelude::*;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 12;
+
+pub struct Day;
+
+impl Solver for Day {
+    /// Each row of springs with the sizes of its groups of damaged springs.
+    type Model = Vec<(String, Vec<usize>)>;
+
+    fn parse(text: &str) -> Result<Self::Model, PuzzleError> {
+        let input = Input::new(DAY, text);
+        input
+            .lines()
+            .map(|line| {
+                let mut parts = line.split_whitespace();
+                let (Some(pattern), Some(counts)) = (parts.next(), parts.next()) else {
+                    return Err(input.error(line, "expected 'springs counts'"));
+                };
+                if let Some((i, c)) = pattern
+                    .char_indices()
+                    .find(|&(_, c)| !matches!(c, '#' | '.' | '?'))
+                {
+                    return Err(input.error(&pattern[i..i + c.len_utf8()], "invalid spring"));
+                }
+                let counts = counts
+                    .split(',')
+                    .map(|x| match input.parse::<usize>(x)? {
+                        0 => Err(input.error(x, "group of damaged springs is empty")),
+                        count => Ok(count),
+                    })
+                    .collect::<Result<Vec<usize>, PuzzleError>>()?;
+                Ok((pattern.to_string(), counts))
+            })
+            .collect()
+    }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.parse_input()
+    fn solve_part_1(rows: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(rows
             .iter()
             .map(|(pattern, counts)| count_arrangements(pattern, counts))
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 
-    fn solve_part_2(&self) -> String {
-        self.parse_input()
+    fn solve_part_2(rows: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(rows
             .par_iter()
             .map(|(pattern, counts)| {
-                let pattern = [*pattern; 5].join("?");
+                let pattern = [pattern.as_str(); 5].join("?");
                 let counts = counts.repeat(5);
                 count_arrangements(&pattern, &counts)
             })
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn parse_input(&self) -> Vec<(&str, Vec<usize>)> {
-        self.input
-            .lines()
-            .map(|line| {
-                let mut parts = line.split_whitespace();
-                let pattern = parts.next().unwrap();
-                let counts = parts
-                    .next()
-                    .unwrap()
-                    .split(',')
-                    .map(|x| x.parse::<usize>().unwrap())
-                    .collect();
-                (pattern, counts)
-            })
-            .collect()
-    }
-}
-
-fn count_arrangements(line: &str, counts: &[usize]) -> usize {
+pub fn count_arrangements(line: &str, counts: &[usize]) -> usize {
     let line = line.as_bytes();
     let n = line.len();
     let m = counts.len();
+    if counts.iter().sum::<usize>() + m - 1 > n {
+        return 0;
+    }
     let mut dp = &mut vec![vec![0; n + 1]; m + 1];
     let mut next_dp = &mut vec![vec![0; n + 1]; m + 1];
 
@@ -97,15 +105,16 @@ mod tests {
             ????.#...#... 4,1,1\n\
             ????.######..#####. 1,6,5\n\
             ?###???????? 3,2,1";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "21");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(21));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/12")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "7541");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/12")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(7541));
     }
 
     #[test]
@@ -116,14 +125,26 @@ mod tests {
             ????.#...#... 4,1,1\n\
             ????.######..#####. 1,6,5\n\
             ?###???????? 3,2,1";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "525152");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(525152));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/12")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "17485169859432");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/12")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(17485169859432));
+    }
+
+    #[test]
+    fn test_invalid_spring() {
+        let location = Day::parse("???é.### 1,1,3")
+            .err()
+            .unwrap()
+            .location
+            .unwrap();
+        assert_eq!((location.line, location.column), (1, 4));
+        assert_eq!(location.text, "é");
     }
 }
@@ -1,58 +1,81 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, Location, PuzzleError, Solver};
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 13;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.solve_generic(0).to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        self.solve_generic(1).to_string()
-    }
+/// A pattern of ash and rocks, with each row and column packed into the bits of an integer.
+pub struct Pattern {
+    pub rows: Vec<u64>,
+    pub cols: Vec<u64>,
+    /// Where the pattern is in the input, to report a pattern without a line of reflection.
+    pub location: Option<Location>,
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+impl Solver for Day {
+    type Model = Vec<Pattern>;
+
+    fn parse(text: &str) -> Result<Vec<Pattern>, PuzzleError> {
+        let input = Input::new(DAY, text);
+        text.split("\n\n")
+            .map(|block| {
+                let (rows, cols) = parse_mirror(input, block)?;
+                Ok(Pattern {
+                    rows,
+                    cols,
+                    location: input.location(block),
+                })
+            })
+            .collect()
     }
 
-    fn parse_mirrors(&self) -> Vec<(Vec<u64>, Vec<u64>)> {
-        self.input.split("\n\n").map(parse_mirror).collect()
+    fn solve_part_1(patterns: &Vec<Pattern>) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(patterns, 0)?.into())
     }
 
-    fn solve_generic(&self, expected_diffs: u32) -> usize {
-        self.parse_mirrors()
-            .iter()
-            .map(|(rows, cols)| {
-                reflection_score(rows, expected_diffs, 100)
-                    .or_else(|| reflection_score(cols, expected_diffs, 1))
-                    .unwrap()
-            })
-            .sum::<usize>()
+    fn solve_part_2(patterns: &Vec<Pattern>) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(patterns, 1)?.into())
     }
 }
 
-fn parse_mirror(block: &str) -> (Vec<u64>, Vec<u64>) {
+fn solve_generic(patterns: &[Pattern], expected_diffs: u32) -> Result<usize, PuzzleError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            reflection_score(&pattern.rows, expected_diffs, 100)
+                .or_else(|| reflection_score(&pattern.cols, expected_diffs, 1))
+                .ok_or_else(|| PuzzleError {
+                    day: DAY,
+                    location: pattern.location.clone(),
+                    message: "pattern has no line of reflection".to_string(),
+                })
+        })
+        .sum::<Result<usize, PuzzleError>>()
+}
+
+fn parse_mirror(input: Input, block: &str) -> Result<(Vec<u64>, Vec<u64>), PuzzleError> {
     let grid: Vec<Vec<u64>> = block
         .lines()
         .map(|line| line.chars().map(|c| if c == '#' { 1 } else { 0 }).collect())
         .collect();
+    let width = grid.first().map_or(0, Vec::len);
+    if width == 0 || width > 64 || grid.len() > 64 || grid.iter().any(|row| row.len() != width) {
+        return Err(input.error(
+            block,
+            "pattern must be a non-empty rectangle at most 64 tiles across",
+        ));
+    }
     let rows = grid
         .iter()
         .map(|row| row.iter().fold(0, |acc, &bit| (acc << 1) | bit))
         .collect::<Vec<u64>>();
-    let cols = (0..grid[0].len())
+    let cols = (0..width)
         .map(|i| grid.iter().fold(0, |acc, row| (acc << 1) | row[i]))
         .collect::<Vec<u64>>();
-    (rows, cols)
+    Ok((rows, cols))
 }
 
-fn reflection_score(values: &[u64], expected_diffs: u32, factor: usize) -> Option<usize> {
+pub fn reflection_score(values: &[u64], expected_diffs: u32, factor: usize) -> Option<usize> {
     for i in 0..values.len() - 1 {
         let mut diffs = 0;
         for (j, k) in (0..=i).rev().zip((i + 1)..values.len()) {
@@ -90,15 +113,16 @@ mod tests {
             #####.##.\n\
             ..##..###\n\
             #....#..#";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "405");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(405));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/13")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "27202");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/13")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(27202));
     }
 
     #[test]
@@ -118,14 +142,15 @@ mod tests {
             #####.##.\n\
             ..##..###\n\
             #....#..#";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "400");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(400));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/13")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "41566");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/13")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(41566));
     }
 }
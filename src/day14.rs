@@ -1,172 +1,75 @@
-use crate::day14::Direction::{East, North, South, West};
-use crate::puzzle::Puzzle;
-use std::collections::HashMap;
+use crate::cycles;
+use crate::grid::{Direction, Grid};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 14;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let mut grid = self.parse_grid();
-        tilt_grid(&mut grid, North);
-        total_load(&grid).to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        let target = 1_000_000_000;
-        let mut steps = 0;
-        let mut grid = self.parse_grid();
-        let mut seen = HashMap::new();
-        while steps < target {
-            tilt_grid(&mut grid, North);
-            tilt_grid(&mut grid, West);
-            tilt_grid(&mut grid, South);
-            tilt_grid(&mut grid, East);
-            steps += 1;
-            match seen.get(&grid) {
-                Some(&prev_steps) => {
-                    let cycle_length = steps - prev_steps;
-                    let cycles_to_skip = (target - steps) / cycle_length;
-                    steps += cycles_to_skip * cycle_length;
-                }
-                None => {
-                    seen.insert(grid.clone(), steps);
-                }
-            }
-        }
-        total_load(&grid).to_string()
+impl Solver for Day {
+    type Model = Grid<char>;
+
+    fn parse(text: &str) -> Result<Grid<char>, PuzzleError> {
+        Grid::parse(Input::new(DAY, text), "O#.")
     }
-}
 
-#[derive(Eq, PartialEq, Copy, Clone)]
-enum Direction {
-    North,
-    South,
-    West,
-    East,
-}
+    fn solve_part_1(grid: &Grid<char>) -> Result<Answer, PuzzleError> {
+        let mut grid = grid.clone();
+        tilt(&mut grid, Direction::North);
+        Ok(total_load(&grid).into())
+    }
 
-struct TiltConfig {
-    occupied_init: i32,
-    occupied_step: i32,
-    major_start: i32,
-    major_end: i32,
-    major_step: i32,
-    minor: usize,
-    row_major: bool,
+    fn solve_part_2(grid: &Grid<char>) -> Result<Answer, PuzzleError> {
+        let grid = cycles::nth_state(grid, spin_cycle, 1_000_000_000);
+        Ok(total_load(&grid).into())
+    }
 }
 
-fn tilt_grid(grid: &mut [Vec<char>], dir: Direction) {
-    let (rows, cols) = (grid.len(), grid[0].len());
-    match dir {
-        North => (0..cols).for_each(|col| {
-            tilt_grid_impl(
-                grid,
-                TiltConfig {
-                    occupied_init: -1,
-                    occupied_step: 1,
-                    major_start: 0,
-                    major_end: rows as i32,
-                    major_step: 1,
-                    minor: col,
-                    row_major: true,
-                },
-            )
-        }),
-        South => (0..cols).for_each(|col| {
-            tilt_grid_impl(
-                grid,
-                TiltConfig {
-                    occupied_init: cols as i32,
-                    occupied_step: -1,
-                    major_start: (rows - 1) as i32,
-                    major_end: -1,
-                    major_step: -1,
-                    minor: col,
-                    row_major: true,
-                },
-            )
-        }),
-        West => (0..rows).for_each(|row| {
-            tilt_grid_impl(
-                grid,
-                TiltConfig {
-                    occupied_init: -1,
-                    occupied_step: 1,
-                    major_start: 0,
-                    major_end: cols as i32,
-                    major_step: 1,
-                    minor: row,
-                    row_major: false,
-                },
-            )
-        }),
-        East => (0..rows).for_each(|row| {
-            tilt_grid_impl(
-                grid,
-                TiltConfig {
-                    occupied_init: rows as i32,
-                    occupied_step: -1,
-                    major_start: (cols - 1) as i32,
-                    major_end: -1,
-                    major_step: -1,
-                    minor: row,
-                    row_major: false,
-                },
-            )
-        }),
-    };
+/// Tilts the platform north, west, south and then east.
+pub fn spin_cycle(grid: &mut Grid<char>) {
+    for direction in [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ] {
+        tilt(grid, direction);
+    }
 }
 
-fn tilt_grid_impl(grid: &mut [Vec<char>], config: TiltConfig) {
-    let mut occupied = config.occupied_init;
-    let mut major = config.major_start;
-    while major != config.major_end {
-        let (row, col) = if config.row_major {
-            (major as usize, config.minor)
-        } else {
-            (config.minor, major as usize)
-        };
-        match grid[row][col] {
-            'O' => {
-                occupied += config.occupied_step;
-                grid[row][col] = '.';
-                if config.row_major {
-                    grid[occupied as usize][config.minor] = 'O';
-                } else {
-                    grid[config.minor][occupied as usize] = 'O';
-                };
+/// Tilts the platform so that every rounded rock ('O') rolls as far as it can in the direction.
+pub fn tilt(grid: &mut Grid<char>, direction: Direction) {
+    let back = direction.reverse();
+    // Each lane of tiles is walked from the edge the rocks roll towards, keeping track of the
+    // nearest tile a rock can roll to.
+    let edge: Vec<_> = grid
+        .points()
+        .filter(|&point| !grid.contains(point.step(direction)))
+        .collect();
+    for mut point in edge {
+        let mut free = point;
+        while let Some(&tile) = grid.get(point) {
+            match tile {
+                'O' => {
+                    grid[point] = '.';
+                    grid[free] = 'O';
+                    free = free.step(back);
+                }
+                '#' => free = point.step(back),
+                _ => {}
             }
-            '#' => occupied = major,
-            _ => {}
+            point = point.step(back);
         }
-        major += config.major_step;
     }
 }
 
-fn total_load(grid: &Vec<Vec<char>>) -> usize {
+pub fn total_load(grid: &Grid<char>) -> usize {
     grid.iter()
-        .enumerate()
-        .map(|(i, row)| row.iter().filter(|&&c| c == 'O').count() * (grid.len() - i))
+        .filter(|(_, &tile)| tile == 'O')
+        .map(|(point, _)| grid.rows() - point.row as usize)
         .sum()
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn parse_grid(&self) -> Vec<Vec<char>> {
-        self.input
-            .lines()
-            .map(|line| line.chars().collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,15 +87,16 @@ mod tests {
             .......O..\n\
             #....###..\n\
             #OO..#....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "136");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(136));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/14")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "113486");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/14")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(113486));
     }
 
     #[test]
@@ -207,14 +111,15 @@ mod tests {
             .......O..\n\
             #....###..\n\
             #OO..#....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "64");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(64));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/14")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "104409");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/14")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(104409));
     }
 }
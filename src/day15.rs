@@ -1,37 +1,44 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, PuzzleError, Solver};
 use lazy_regex::regex_captures;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 15;
+
+pub struct Day;
+
+impl Solver for Day {
+    /// The steps of the initialization sequence, with newlines ignored.
+    type Model = Vec<String>;
+
+    fn parse(input: &str) -> Result<Vec<String>, PuzzleError> {
+        Ok(input.split(',').map(|s| s.replace('\n', "")).collect())
+    }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        self.parse_steps()
+    fn solve_part_1(steps: &Vec<String>) -> Result<Answer, PuzzleError> {
+        Ok(steps
             .iter()
             .map(|s| hash_algorithm(s))
             .sum::<usize>()
-            .to_string()
+            .into())
     }
 
-    fn solve_part_2(&self) -> String {
+    fn solve_part_2(steps: &Vec<String>) -> Result<Answer, PuzzleError> {
         let mut boxes: Vec<Vec<(String, usize)>> = vec![vec![]; 256];
-        for s in self.parse_steps() {
-            let (_, label, operation, digit) = regex_captures!(r"(\S+)(=|-)(\d+)?", &s).unwrap();
+        for s in steps {
+            let invalid_step = || PuzzleError::new(DAY, format!("invalid step '{}'", s));
+            let (_, label, operation, digit) =
+                regex_captures!(r"^(\S+?)(=(\d+)|-)$", s).ok_or_else(invalid_step)?;
             let hash = hash_algorithm(label);
-            match operation {
-                "-" => boxes[hash].retain(|(l, _)| l != label),
-                "=" => {
-                    let digit = digit.parse::<usize>().unwrap();
-                    match boxes[hash].iter_mut().find(|(l, _)| l == label) {
-                        Some((_, d)) => *d = digit,
-                        None => boxes[hash].push((label.to_string(), digit)),
-                    }
+            if operation == "-" {
+                boxes[hash].retain(|(l, _)| l != label);
+            } else {
+                let digit = digit.parse::<usize>().map_err(|_| invalid_step())?;
+                match boxes[hash].iter_mut().find(|(l, _)| l == label) {
+                    Some((_, d)) => *d = digit,
+                    None => boxes[hash].push((label.to_string(), digit)),
                 }
-                _ => unreachable!(),
-            };
+            }
         }
-        boxes
+        Ok(boxes
             .iter()
             .enumerate()
             .flat_map(|(i, b)| {
@@ -40,23 +47,11 @@ impl Puzzle for Day {
                     .map(move |(j, (_, d))| (i + 1) * (j + 1) * d)
             })
             .sum::<usize>()
-            .to_string()
-    }
-}
-
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn parse_steps(&self) -> Vec<String> {
-        self.input.split(',').map(|s| s.replace('\n', "")).collect()
+            .into())
     }
 }
 
-fn hash_algorithm(s: &str) -> usize {
+pub fn hash_algorithm(s: &str) -> usize {
     s.bytes().fold(0, |hash, c| (hash + c as usize) * 17 % 256)
 }
 
@@ -68,28 +63,30 @@ mod tests {
     #[test]
     fn test_part_1_example_1() {
         let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "1320");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(1320));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/15")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "511215");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/15")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(511215));
     }
 
     #[test]
     fn test_part_2_example_1() {
         let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "145");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(145));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/15")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "236057");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/15")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(236057));
     }
 }
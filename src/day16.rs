@@ -1,103 +1,73 @@
-use crate::puzzle::Puzzle;
+use crate::grid::{Direction, Grid, Point};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use rayon::prelude::*;
-use std::collections::HashSet;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 16;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let grid = self.parse_grid();
-        count_tiles_energized(&grid, (0, 0, 0, 1)).to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        let grid = self.parse_grid();
-        let (rows, cols) = (grid.len() as i32, grid[0].len() as i32);
-        let mut beams = Vec::new();
-        beams.extend((0..cols).map(|col| (0, col, 1, 0)).collect::<Vec<_>>());
-        beams.extend(
-            (0..cols)
-                .map(|col| (rows - 1, col, -1, 0))
-                .collect::<Vec<_>>(),
-        );
-        beams.extend((0..rows).map(|row| (row, 0, 0, 1)).collect::<Vec<_>>());
-        beams.extend(
-            (0..rows)
-                .map(|row| (row, cols - 1, 0, -1))
-                .collect::<Vec<_>>(),
-        );
-        beams
-            .par_iter()
-            .map(|&start| count_tiles_energized(&grid, start))
-            .max()
-            .unwrap()
-            .to_string()
+impl Solver for Day {
+    type Model = Grid<char>;
+
+    fn parse(text: &str) -> Result<Grid<char>, PuzzleError> {
+        Grid::parse(Input::new(DAY, text), "./\\|-")
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_1(grid: &Grid<char>) -> Result<Answer, PuzzleError> {
+        Ok(count_tiles_energized(grid, Point::new(0, 0), Direction::East).into())
     }
 
-    fn parse_grid(&self) -> Vec<Vec<char>> {
-        self.input
-            .lines()
-            .map(|line| line.chars().collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>()
+    fn solve_part_2(grid: &Grid<char>) -> Result<Answer, PuzzleError> {
+        let (rows, cols) = (grid.rows() as isize, grid.cols() as isize);
+        let mut beams = Vec::new();
+        beams.extend((0..cols).map(|col| (Point::new(0, col), Direction::South)));
+        beams.extend((0..cols).map(|col| (Point::new(rows - 1, col), Direction::North)));
+        beams.extend((0..rows).map(|row| (Point::new(row, 0), Direction::East)));
+        beams.extend((0..rows).map(|row| (Point::new(row, cols - 1), Direction::West)));
+        Ok(beams
+            .par_iter()
+            .map(|&(start, direction)| count_tiles_energized(grid, start, direction))
+            .max()
+            .unwrap_or(0)
+            .into())
     }
 }
 
-fn count_tiles_energized(grid: &[Vec<char>], start: (i32, i32, i32, i32)) -> usize {
-    let (rows, cols) = (grid.len(), grid[0].len());
-    let mut beams = vec![start];
-    let mut energized = HashSet::new();
-    while let Some((row, col, drow, dcol)) = beams.pop() {
-        if row < 0 || row >= rows as i32 || col < 0 || col >= cols as i32 {
+/// Returns how many tiles a beam entering the given tile in the given direction passes through.
+pub fn count_tiles_energized(grid: &Grid<char>, start: Point, direction: Direction) -> usize {
+    // The directions each tile has been entered in, as one bit per direction.
+    let mut entered = Grid::filled(grid.rows(), grid.cols(), 0u8);
+    let mut beams = vec![(start, direction)];
+    while let Some((position, direction)) = beams.pop() {
+        let Some(seen) = entered.get_mut(position) else {
             continue;
-        }
-        if !energized.insert((row, col, drow, dcol)) {
+        };
+        if *seen & (1 << direction as u8) != 0 {
             continue;
         }
+        *seen |= 1 << direction as u8;
         // Check what the beam is hitting.
-        match grid[row as usize][col as usize] {
-            '.' => {
-                beams.push((row + drow, col + dcol, drow, dcol));
-            }
-            '/' => {
-                beams.push((row - dcol, col - drow, -dcol, -drow));
+        let directions = match (grid[position], direction) {
+            ('/', Direction::North | Direction::South) => [Some(direction.turn_right()), None],
+            ('/', _) => [Some(direction.turn_left()), None],
+            ('\\', Direction::North | Direction::South) => [Some(direction.turn_left()), None],
+            ('\\', _) => [Some(direction.turn_right()), None],
+            ('|', direction) if !direction.is_vertical() => {
+                [Some(Direction::North), Some(Direction::South)]
             }
-            '\\' => {
-                beams.push((row + dcol, col + drow, dcol, drow));
+            ('-', direction) if direction.is_vertical() => {
+                [Some(Direction::East), Some(Direction::West)]
             }
-            '|' => {
-                if dcol == 0 {
-                    beams.push((row + drow, col + dcol, drow, dcol));
-                } else {
-                    beams.push((row + 1, col, 1, 0));
-                    beams.push((row - 1, col, -1, 0));
-                }
-            }
-            '-' => {
-                if drow == 0 {
-                    beams.push((row + drow, col + dcol, drow, dcol));
-                } else {
-                    beams.push((row, col + 1, 0, 1));
-                    beams.push((row, col - 1, 0, -1));
-                }
-            }
-            _ => (),
-        }
+            _ => [Some(direction), None],
+        };
+        beams.extend(
+            directions
+                .into_iter()
+                .flatten()
+                .map(|direction| (position.step(direction), direction)),
+        );
     }
-    energized
-        .iter()
-        .map(|(row, col, _, _)| (row, col))
-        .collect::<HashSet<_>>()
-        .len()
+    entered.iter().filter(|(_, &seen)| seen != 0).count()
 }
 
 #[cfg(test)]
@@ -117,15 +87,16 @@ mod tests {
             .-.-/..|..\n\
             .|....-|.\\\n\
             ..//.|....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "46");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(46));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/16")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "7482");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/16")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(7482));
     }
 
     #[test]
@@ -140,14 +111,15 @@ mod tests {
             .-.-/..|..\n\
             .|....-|.\\\n\
             ..//.|....";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "51");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(51));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/16")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "7896");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/16")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(7896));
     }
 }
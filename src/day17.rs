@@ -1,114 +1,95 @@
-use crate::puzzle::Puzzle;
+use crate::grid::{Direction, Grid, Point};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap};
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 17;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        least_heat_loss::<1, 3>(&self.parse_grid()).to_string()
-    }
+pub struct Day;
+
+impl Solver for Day {
+    type Model = Grid<usize>;
 
-    fn solve_part_2(&self) -> String {
-        least_heat_loss::<4, 10>(&self.parse_grid()).to_string()
+    fn parse(text: &str) -> Result<Grid<usize>, PuzzleError> {
+        Ok(Grid::parse(Input::new(DAY, text), "0123456789")?
+            .map(|&c| c.to_digit(10).unwrap() as usize))
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_1(grid: &Grid<usize>) -> Result<Answer, PuzzleError> {
+        Ok(least_heat_loss::<1, 3>(grid)?.into())
     }
 
-    fn parse_grid(&self) -> Vec<Vec<usize>> {
-        self.input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap() as usize)
-                    .collect::<Vec<usize>>()
-            })
-            .collect::<Vec<Vec<usize>>>()
+    fn solve_part_2(grid: &Grid<usize>) -> Result<Answer, PuzzleError> {
+        Ok(least_heat_loss::<4, 10>(grid)?.into())
     }
 }
 
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
 struct LavaFlow {
-    loc: (isize, isize),
-    dir: (isize, isize),
+    loc: Point,
+    dir: Direction,
     count: usize,
 }
 
-fn least_heat_loss<const MIN_STEPS: usize, const MAX_STEPS: usize>(grid: &[Vec<usize>]) -> usize {
-    let (rows, cols) = (grid.len(), grid[0].len());
-    let start = (0, 0);
-    let end = (rows as isize - 1, cols as isize - 1);
+pub fn least_heat_loss<const MIN_STEPS: usize, const MAX_STEPS: usize>(
+    grid: &Grid<usize>,
+) -> Result<usize, PuzzleError> {
+    let start = Point::new(0, 0);
+    let end = Point::new(grid.rows() as isize - 1, grid.cols() as isize - 1);
 
     let mut score = HashMap::new();
 
     let mut queue = BinaryHeap::new();
-    queue.push((
-        Reverse((0, 0)),
-        LavaFlow {
-            loc: start,
-            dir: (0, 1),
-            count: 0,
-        },
-    ));
-    queue.push((
-        Reverse((0, 0)),
-        LavaFlow {
-            loc: start,
-            dir: (1, 0),
-            count: 0,
-        },
-    ));
+    for dir in [Direction::East, Direction::South] {
+        queue.push((
+            Reverse((0, 0)),
+            LavaFlow {
+                loc: start,
+                dir,
+                count: 0,
+            },
+        ));
+    }
 
     while let Some((Reverse((_, cost)), flow)) = queue.pop() {
         if flow.loc == end && flow.count >= MIN_STEPS {
-            return cost;
+            return Ok(cost);
         }
-        for new_flow in neighbors::<MIN_STEPS, MAX_STEPS>(rows, cols, flow) {
-            let new_cost = cost + grid[new_flow.loc.0 as usize][new_flow.loc.1 as usize];
+        for new_flow in neighbors::<MIN_STEPS, MAX_STEPS>(grid, flow) {
+            let new_cost = cost + grid[new_flow.loc];
             if new_cost < score.get(&new_flow).copied().unwrap_or(usize::MAX) {
-                let heuristic = new_cost
-                    + (end.0 - new_flow.loc.0).unsigned_abs()
-                    + (end.1 - new_flow.loc.1).unsigned_abs();
+                let heuristic = new_cost + new_flow.loc.manhattan_distance(end);
                 score.insert(new_flow, new_cost);
                 queue.push((Reverse((heuristic, new_cost)), new_flow));
             }
         }
     }
 
-    unreachable!()
+    Err(PuzzleError::new(
+        DAY,
+        "the crucible cannot reach the bottom-right corner",
+    ))
 }
 
 fn neighbors<const MIN_STEPS: usize, const MAX_STEPS: usize>(
-    rows: usize,
-    cols: usize,
+    grid: &Grid<usize>,
     flow: LavaFlow,
-) -> impl Iterator<Item = LavaFlow> {
-    let (row, col, drow, dcol, count) =
-        (flow.loc.0, flow.loc.1, flow.dir.0, flow.dir.1, flow.count);
-    let in_bounds = move |r, c| r >= 0 && r < rows as isize && c >= 0 && c < cols as isize;
-    [(drow, dcol), (dcol, -drow), (-dcol, drow)]
+) -> impl Iterator<Item = LavaFlow> + '_ {
+    [flow.dir, flow.dir.turn_right(), flow.dir.turn_left()]
         .into_iter()
-        .filter_map(move |(new_drow, new_dcol)| {
-            let is_turn = new_drow != drow || new_dcol != dcol;
-            let (new_row, new_col) = (row + new_drow, col + new_dcol);
-            if (is_turn && count < MIN_STEPS)
-                || (!is_turn && count == MAX_STEPS)
-                || !in_bounds(new_row, new_col)
+        .filter_map(move |dir| {
+            let is_turn = dir != flow.dir;
+            let loc = flow.loc.step(dir);
+            if (is_turn && flow.count < MIN_STEPS)
+                || (!is_turn && flow.count == MAX_STEPS)
+                || !grid.contains(loc)
             {
                 None
             } else {
                 Some(LavaFlow {
-                    loc: (new_row, new_col),
-                    dir: (new_drow, new_dcol),
-                    count: if is_turn { 1 } else { count + 1 },
+                    loc,
+                    dir,
+                    count: if is_turn { 1 } else { flow.count + 1 },
                 })
             }
         })
@@ -134,15 +115,16 @@ mod tests {
             1224686865563\n\
             2546548887735\n\
             4322674655533";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "102");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(102));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/17")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "758");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/17")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(758));
     }
 
     #[test]
@@ -160,8 +142,8 @@ mod tests {
             1224686865563\n\
             2546548887735\n\
             4322674655533";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "94");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(94));
     }
 
     #[test]
@@ -171,14 +153,24 @@ mod tests {
             999999999991\n\
             999999999991\n\
             999999999991";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "71");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(71));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/17")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "892");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/17")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(892));
+    }
+
+    #[test]
+    fn test_invalid_grid() {
+        let error = Day::parse("123\n4a6").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Day 17, line 2, column 2: invalid tile (found 'a')"
+        );
     }
 }
@@ -1,108 +1,75 @@
-use crate::puzzle::Puzzle;
+use crate::geometry::{Polygon, PolygonError};
+use crate::grid::Direction;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex_captures;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 18;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let plan = self.parse_part_1();
-        solve_generic(plan).to_string()
-    }
+pub struct Day;
 
-    fn solve_part_2(&self) -> String {
-        let plan = self.parse_part_2();
-        solve_generic(plan).to_string()
-    }
+/// The dig plan as read for each part: from the directions and distances, and from the colors.
+pub struct DigPlans {
+    pub part_1: Vec<(Direction, u32)>,
+    pub part_2: Vec<(Direction, u32)>,
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn parse_part_1(&self) -> Vec<(Point, u32)> {
-        self.input
-            .lines()
-            .map(|line| {
-                let mut parts = line.split_whitespace();
-                let dir = match parts.next().unwrap() {
-                    "U" => UP,
-                    "D" => DOWN,
-                    "L" => LEFT,
-                    "R" => RIGHT,
-                    _ => unreachable!(),
-                };
-                let dist = parts.next().unwrap().parse::<u32>().unwrap();
-                (dir, dist)
-            })
-            .collect()
-    }
-
-    fn parse_part_2(&self) -> Vec<(Point, u32)> {
-        self.input
-            .lines()
-            .map(|line| {
-                let (_, dist, dir) = regex_captures!(r".* \(#([a-f0-9]{5})(\d)\)", line).unwrap();
-                let dir = match dir {
-                    "0" => RIGHT,
-                    "1" => DOWN,
-                    "2" => LEFT,
-                    "3" => UP,
-                    _ => unreachable!(),
-                };
-                let dist = u32::from_str_radix(dist, 16).unwrap();
-                (dir, dist)
-            })
-            .collect()
+impl Solver for Day {
+    type Model = DigPlans;
+
+    fn parse(text: &str) -> Result<DigPlans, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut plans = DigPlans {
+            part_1: Vec::new(),
+            part_2: Vec::new(),
+        };
+        for line in input.lines() {
+            let (_, dir, dist, color_dist, color_dir) =
+                regex_captures!(r"^([UDLR]) (\S+) \(#([a-f0-9]{5})([0-3])\)", line)
+                    .ok_or_else(|| input.error(line, "expected 'DIRECTION DISTANCE (#COLOR)'"))?;
+            let dir = match dir {
+                "U" => Direction::North,
+                "D" => Direction::South,
+                "L" => Direction::West,
+                _ => Direction::East,
+            };
+            plans.part_1.push((dir, input.parse::<u32>(dist)?));
+            let color_dir = match color_dir {
+                "0" => Direction::East,
+                "1" => Direction::South,
+                "2" => Direction::West,
+                _ => Direction::North,
+            };
+            // Five hex digits always fit in a u32.
+            let color_dist = u32::from_str_radix(color_dist, 16).unwrap();
+            plans.part_2.push((color_dir, color_dist));
+        }
+        Ok(plans)
     }
-}
 
-fn solve_generic(plan: Vec<(Point, u32)>) -> u64 {
-    let mut boundary = Point { row: 0, col: 0 };
-    let mut perimeter = 0u64;
-    let mut area = 0i128;
-    for (dir, dist) in plan {
-        let position = boundary + dir * dist;
-        area += boundary.row * position.col - boundary.col * position.row;
-        perimeter += dist as u64;
-        boundary = position;
+    fn solve_part_1(plans: &DigPlans) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(&plans.part_1)?.into())
     }
-    (area.unsigned_abs() as u64 - perimeter) / 2 + perimeter + 1
-}
-
-const UP: Point = Point { row: -1, col: 0 };
-const DOWN: Point = Point { row: 1, col: 0 };
-const LEFT: Point = Point { row: 0, col: 1 };
-const RIGHT: Point = Point { row: 0, col: -1 };
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
-struct Point {
-    row: i128,
-    col: i128,
-}
-
-impl std::ops::Add for Point {
-    type Output = Point;
-    fn add(self, other: Point) -> Point {
-        Point {
-            row: self.row + other.row,
-            col: self.col + other.col,
-        }
+    fn solve_part_2(plans: &DigPlans) -> Result<Answer, PuzzleError> {
+        Ok(solve_generic(&plans.part_2)?.into())
     }
 }
 
-impl std::ops::Mul<u32> for Point {
-    type Output = Point;
-    fn mul(self, other: u32) -> Point {
-        Point {
-            row: self.row * other as i128,
-            col: self.col * other as i128,
-        }
+/// Returns how many cubic meters of lava the lagoon dug by the plan holds, counting the trench
+/// around its edge.
+pub fn solve_generic(plan: &[(Direction, u32)]) -> Result<u128, PuzzleError> {
+    let lagoon = Polygon::from_steps(plan.iter().map(|&(dir, dist)| (dir, dist as u64))).map_err(
+        |e| match e {
+            PolygonError::NotClosed => {
+                PuzzleError::new(DAY, "the dig plan does not return to where it started")
+            }
+            PolygonError::Overflow => PuzzleError::new(DAY, e),
+        },
+    )?;
+    if lagoon.is_self_intersecting() {
+        return Err(PuzzleError::new(DAY, "the dig plan crosses its own trench"));
     }
+    Ok(lagoon.enclosed_points())
 }
 
 #[cfg(test)]
@@ -126,15 +93,16 @@ mod tests {
             U 3 (#a77fa3)\n\
             L 2 (#015232)\n\
             U 2 (#7a21e3)";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "62");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(62));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/18")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "47139");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/18")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(47139));
     }
 
     #[test]
@@ -153,14 +121,24 @@ mod tests {
             U 3 (#a77fa3)\n\
             L 2 (#015232)\n\
             U 2 (#7a21e3)";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "952408144115");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(952408144115));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/18")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "173152345887206");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/18")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(173152345887206));
+    }
+
+    #[test]
+    fn test_invalid_plans() {
+        let open = "R 2 (#000020)\nD 2 (#000021)";
+        assert!(Day::create(open).unwrap().solve_part_1().is_err());
+        let crossing = "R 2 (#000020)\nD 1 (#000011)\nL 1 (#000012)\nU 2 (#000023)\nL 1 (#000012)\nD 1 (#000011)";
+        let puzzle = Day::create(crossing).unwrap();
+        assert!(puzzle.solve_part_1().is_err());
     }
 }
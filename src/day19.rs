@@ -1,131 +1,145 @@
-use crate::puzzle::Puzzle;
+use crate::intervals::{BoxSet, Hyperrectangle};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex_captures;
 use std::collections::{HashMap, HashSet};
-use std::ops::Range;
 
-pub struct Day {
-    input: String,
+const DAY: u8 = 19;
+
+pub struct Day;
+
+/// The parts to sort, and the ratings that the workflows accept.
+pub struct System {
+    pub parts: Vec<Part>,
+    pub accepted: BoxSet<4>,
 }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let workflows = self.parse_workflows();
-        let intervals = find_accepted_intervals(&workflows);
-        self.parse_parts()
-            .iter()
-            .filter_map(|part| {
-                if intervals.contains(part) {
-                    Some(part.0 + part.1 + part.2 + part.3)
-                } else {
-                    None
-                }
-            })
-            .sum::<usize>()
-            .to_string()
+impl Solver for Day {
+    type Model = System;
+
+    fn parse(text: &str) -> Result<System, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut sections = text.split("\n\n");
+        let (workflows, parts) = sections.next().zip(sections.next()).ok_or_else(|| {
+            PuzzleError::new(
+                DAY,
+                "expected workflows and parts separated by a blank line",
+            )
+        })?;
+        let workflows = parse_workflows(input, workflows)?;
+        Ok(System {
+            parts: parse_parts(input, parts)?,
+            accepted: find_accepted_intervals(&workflows)?,
+        })
     }
 
-    fn solve_part_2(&self) -> String {
-        let workflows = self.parse_workflows();
-        let intervals = find_accepted_intervals(&workflows);
-        intervals.size().to_string()
+    fn solve_part_1(system: &System) -> Result<Answer, PuzzleError> {
+        Ok(system
+            .parts
+            .iter()
+            .filter(|part| system.accepted.contains(part))
+            .map(|part| part.iter().sum::<i64>())
+            .sum::<i64>()
+            .into())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_2(system: &System) -> Result<Answer, PuzzleError> {
+        Ok(system.accepted.volume().into())
     }
+}
 
-    fn parse_workflows(&self) -> HashMap<String, Vec<Rule>> {
-        let mut workflows = HashMap::new();
-        for line in self.input.split("\n\n").next().unwrap().lines() {
-            let (_, name, rules_str) = regex_captures!(r"(\S+)\{(\S+)\}", line).unwrap();
-            let mut rules = Vec::new();
-            for rule_str in rules_str.split(',') {
-                if let Some((_, var, op, value, dst)) =
-                    regex_captures!(r"(x|m|a|s)(<|>)(\d+):(\S+)", rule_str)
-                {
-                    let var = var.chars().next().unwrap();
-                    let op = op.chars().next().unwrap();
-                    let value = value.parse().unwrap();
-                    rules.push(Rule::If(var, op, value, dst.to_string()));
-                } else {
-                    rules.push(Rule::Goto(rule_str.to_string()));
-                }
+pub fn parse_workflows(
+    input: Input,
+    section: &str,
+) -> Result<HashMap<String, Vec<Rule>>, PuzzleError> {
+    let mut workflows = HashMap::new();
+    for line in section.lines() {
+        let (_, name, rules_str) = regex_captures!(r"(\S+)\{(\S+)\}", line)
+            .ok_or_else(|| input.error(line, "expected 'name{rules}'"))?;
+        let mut rules = Vec::new();
+        for rule_str in rules_str.split(',') {
+            if let Some((_, var, op, value, dst)) =
+                regex_captures!(r"(x|m|a|s)(<|>)(\d+):(\S+)", rule_str)
+            {
+                let category = "xmas".find(var).unwrap();
+                let op = op.chars().next().unwrap();
+                let value = input.parse(value)?;
+                rules.push(Rule::If(category, op, value, dst.to_string()));
+            } else {
+                rules.push(Rule::Goto(rule_str.to_string()));
             }
-            workflows.insert(name.to_string(), rules);
         }
-        workflows
+        workflows.insert(name.to_string(), rules);
     }
+    Ok(workflows)
+}
 
-    fn parse_parts(&self) -> Vec<Part> {
-        self.input
-            .split("\n\n")
-            .nth(1)
-            .unwrap()
-            .lines()
-            .map(|line| {
-                let (_, x, m, a, s) =
-                    regex_captures!(r"\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)\}", line).unwrap();
-                (
-                    x.parse().unwrap(),
-                    m.parse().unwrap(),
-                    a.parse().unwrap(),
-                    s.parse().unwrap(),
-                )
-            })
-            .collect()
-    }
+fn parse_parts(input: Input, section: &str) -> Result<Vec<Part>, PuzzleError> {
+    section
+        .lines()
+        .map(|line| {
+            let (_, x, m, a, s) = regex_captures!(r"\{x=(\d+),m=(\d+),a=(\d+),s=(\d+)\}", line)
+                .ok_or_else(|| input.error(line, "expected '{x=N,m=N,a=N,s=N}'"))?;
+            Ok([
+                input.parse(x)?,
+                input.parse(m)?,
+                input.parse(a)?,
+                input.parse(s)?,
+            ])
+        })
+        .collect()
 }
 
-fn find_accepted_intervals(workflows: &HashMap<String, Vec<Rule>>) -> IntervalSet {
+pub fn find_accepted_intervals(
+    workflows: &HashMap<String, Vec<Rule>>,
+) -> Result<BoxSet<4>, PuzzleError> {
     let mut seen = HashSet::new();
-    let mut intervals = Vec::new();
+    let mut accepted = BoxSet::new();
     find_accepted_intervals_impl(
         "in".to_string(),
-        &(1..4001, 1..4001, 1..4001, 1..4001),
+        &Hyperrectangle::new([1..4001, 1..4001, 1..4001, 1..4001]),
         workflows,
         &mut seen,
-        &mut intervals,
-    );
-    IntervalSet::new(intervals)
+        &mut accepted,
+    )?;
+    Ok(accepted)
 }
 
 fn find_accepted_intervals_impl(
     node: String,
-    interval: &Interval,
+    interval: &Hyperrectangle<4>,
     workflows: &HashMap<String, Vec<Rule>>,
     seen_states: &mut HashSet<String>,
-    accepted_intervals: &mut Vec<Interval>,
-) {
-    if seen_states.contains(&node)
-        || node == "R"
-        || interval.0.is_empty()
-        || interval.1.is_empty()
-        || interval.2.is_empty()
-        || interval.3.is_empty()
-    {
-        return;
+    accepted_intervals: &mut BoxSet<4>,
+) -> Result<(), PuzzleError> {
+    if seen_states.contains(&node) || node == "R" || interval.is_empty() {
+        return Ok(());
     }
     if node == "A" {
-        accepted_intervals.push(interval.clone());
-        return;
+        accepted_intervals.insert(interval.clone());
+        return Ok(());
     }
     seen_states.insert(node.clone());
     let mut interval = interval.clone();
-    for rule in workflows.get(&node).unwrap() {
+    let rules = workflows
+        .get(&node)
+        .ok_or_else(|| PuzzleError::new(DAY, format!("no workflow named '{}'", node)))?;
+    for rule in rules {
         match rule {
-            Rule::If(var, op, value, dst) => {
-                let (then_interval, else_interval) = update_interval(*var, *op, *value, interval);
+            Rule::If(category, op, value, dst) => {
+                let (then_interval, else_interval) = match op {
+                    '<' => interval.split(*category, *value),
+                    _ => {
+                        let (below, above) = interval.split(*category, value + 1);
+                        (above, below)
+                    }
+                };
                 find_accepted_intervals_impl(
                     dst.clone(),
                     &then_interval,
                     workflows,
                     seen_states,
                     accepted_intervals,
-                );
+                )?;
                 interval = else_interval
             }
             Rule::Goto(dst) => {
@@ -135,87 +149,22 @@ fn find_accepted_intervals_impl(
                     workflows,
                     seen_states,
                     accepted_intervals,
-                );
+                )?;
             }
         }
     }
+    Ok(())
 }
 
-fn update_interval(var: char, op: char, value: usize, interval: Interval) -> (Interval, Interval) {
-    let mut interval1 = interval.clone();
-    let mut interval2 = interval.clone();
-    match var {
-        'x' => {
-            let (range1, range2) = update_range(op, value, interval.0);
-            interval1.0 = range1;
-            interval2.0 = range2;
-        }
-        'm' => {
-            let (range1, range2) = update_range(op, value, interval.1);
-            interval1.1 = range1;
-            interval2.1 = range2;
-        }
-        'a' => {
-            let (range1, range2) = update_range(op, value, interval.2);
-            interval1.2 = range1;
-            interval2.2 = range2;
-        }
-        's' => {
-            let (range1, range2) = update_range(op, value, interval.3);
-            interval1.3 = range1;
-            interval2.3 = range2;
-        }
-        _ => unreachable!(),
-    }
-    (interval1, interval2)
-}
-
-fn update_range(op: char, value: usize, range: Range<usize>) -> (Range<usize>, Range<usize>) {
-    match op {
-        '<' => (range.start..value, value..range.end),
-        '>' => (value + 1..range.end, range.start..value + 1),
-        _ => unreachable!(),
-    }
-}
-
-enum Rule {
-    If(char, char, usize, String),
+pub enum Rule {
+    /// Sends parts whose rating in a category (as an index into "xmas") compares with a value
+    /// to a workflow.
+    If(usize, char, i64, String),
     Goto(String),
 }
 
-type Part = (usize, usize, usize, usize);
-type Interval = (Range<usize>, Range<usize>, Range<usize>, Range<usize>);
-
-struct IntervalSet {
-    set: Vec<Interval>,
-}
-
-impl IntervalSet {
-    fn new(intervals: Vec<Interval>) -> Self {
-        IntervalSet { set: intervals }
-    }
-
-    fn contains(&self, part: &Part) -> bool {
-        self.set.iter().any(|interval| {
-            interval.0.contains(&part.0)
-                && interval.1.contains(&part.1)
-                && interval.2.contains(&part.2)
-                && interval.3.contains(&part.3)
-        })
-    }
-
-    fn size(&self) -> usize {
-        self.set
-            .iter()
-            .map(|interval| {
-                (interval.0.end - interval.0.start)
-                    * (interval.1.end - interval.1.start)
-                    * (interval.2.end - interval.2.start)
-                    * (interval.3.end - interval.3.start)
-            })
-            .sum::<usize>()
-    }
-}
+/// The ratings of a part, in the order x, m, a, s.
+pub type Part = [i64; 4];
 
 #[cfg(test)]
 mod tests {
@@ -241,15 +190,16 @@ mod tests {
             {x=2036,m=264,a=79,s=2244}\n\
             {x=2461,m=1339,a=466,s=291}\n\
             {x=2127,m=1623,a=2188,s=1013}";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "19114");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(19114));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/19")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "330820");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/19")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(330820));
     }
 
     #[test]
@@ -271,14 +221,23 @@ mod tests {
             {x=2036,m=264,a=79,s=2244}\n\
             {x=2461,m=1339,a=466,s=291}\n\
             {x=2127,m=1623,a=2188,s=1013}";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "167409079868000");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(167409079868000));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/19")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "123972546935551");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/19")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(123972546935551));
+    }
+
+    #[test]
+    fn test_missing_workflow() {
+        let error = Day::parse("in{x<5:A,next}\n\n{x=1,m=2,a=3,s=4}")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "Day 19: no workflow named 'next'");
     }
 }
@@ -1,13 +1,78 @@
-use crate::puzzle::Puzzle;
+use crate::cycles::{align, Periodic};
+use crate::graph::Graph;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use std::collections::{HashMap, VecDeque};
 
-pub struct Day {
-    input: String,
+const DAY: u8 = 20;
+
+pub struct Day;
+
+/// The modules, in their initial state, and which modules send pulses to which.
+pub struct Circuit {
+    pub modules: HashMap<String, Module>,
+    pub wiring: Graph,
 }
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let mut modules = self.parse_modules();
+impl Solver for Day {
+    type Model = Circuit;
+
+    fn parse(text: &str) -> Result<Circuit, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut wiring = Graph::new();
+        let mut modules: HashMap<String, Module> = HashMap::new();
+        for line in input.lines() {
+            let (full_name, outputs) = line
+                .split_once(" -> ")
+                .ok_or_else(|| input.error(line, "expected 'module -> outputs'"))?;
+            let name = if full_name.starts_with('%') || full_name.starts_with('&') {
+                full_name[1..].to_string()
+            } else {
+                full_name.to_string()
+            };
+            let outputs = outputs
+                .split(", ")
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>();
+            let src = wiring.node(&name);
+            for output in &outputs {
+                let dst = wiring.node(output);
+                wiring.add_edge(src, dst, ());
+            }
+            if full_name.starts_with('%') {
+                modules.insert(
+                    name.clone(),
+                    Module::FlipFlop {
+                        name,
+                        enabled: false,
+                        outputs,
+                    },
+                );
+            } else if full_name.starts_with('&') {
+                modules.insert(
+                    name.clone(),
+                    Module::Conjunction {
+                        name,
+                        memory: HashMap::new(),
+                        outputs,
+                    },
+                );
+            } else {
+                modules.insert(name.clone(), Module::Broadcast { name, outputs });
+            };
+        }
+        for (name, module) in modules.iter_mut() {
+            if let Module::Conjunction { memory, .. } = module {
+                let node = wiring.id(name).unwrap();
+                for (input, _) in wiring.predecessors(node) {
+                    memory.insert(wiring.key(input).clone(), Pulse::Low);
+                }
+            }
+        }
+        Ok(Circuit { modules, wiring })
+    }
+
+    fn solve_part_1(circuit: &Circuit) -> Result<Answer, PuzzleError> {
+        let mut modules = circuit.modules.clone();
         let mut low_pulses = 0;
         let mut high_pulses = 0;
         let mut queue = VecDeque::new();
@@ -27,24 +92,25 @@ impl Puzzle for Day {
                 }
             }
         }
-        (low_pulses * high_pulses).to_string()
+        Ok((low_pulses * high_pulses).into())
     }
 
-    fn solve_part_2(&self) -> String {
-        let mut modules = self.parse_modules();
-        let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
-        for (src, module) in &modules {
-            let dsts = match module {
-                Module::FlipFlop { outputs, .. } => outputs,
-                Module::Conjunction { outputs, .. } => outputs,
-                Module::Broadcast { outputs, .. } => outputs,
-            };
-            for dst in dsts {
-                inputs.entry(dst.clone()).or_default().push(src.clone());
-            }
+    fn solve_part_2(circuit: &Circuit) -> Result<Answer, PuzzleError> {
+        let mut modules = circuit.modules.clone();
+        let wiring = &circuit.wiring;
+        let rx_input = wiring
+            .id("rx")
+            .and_then(|rx| wiring.predecessors(rx).next())
+            .map(|(rx_input, _)| rx_input)
+            .ok_or_else(|| PuzzleError::new(DAY, "no module sends pulses to 'rx'"))?;
+        let num_cycles = wiring.in_degree(rx_input);
+        let rx_input = wiring.key(rx_input);
+        if num_cycles == 0 {
+            return Err(PuzzleError::new(
+                DAY,
+                format!("no module sends pulses to '{}'", rx_input),
+            ));
         }
-        let rx_input = inputs.get("rx").unwrap().first().unwrap();
-        let num_cycles = inputs.get(rx_input).unwrap().len();
         let mut cycles = HashMap::new();
         let mut total_presses = 0u64;
         let mut queue = VecDeque::new();
@@ -59,11 +125,15 @@ impl Puzzle for Day {
                 if message.dst == *rx_input && message.pulse == Pulse::High {
                     cycles.insert(message.src.clone(), total_presses);
                     if cycles.len() == num_cycles {
-                        return cycles
+                        // Each input sends a high pulse every so many presses, so rx gets a
+                        // low pulse when they all do at once.
+                        let inputs: Vec<Periodic> = cycles
                             .into_values()
-                            .reduce(num::integer::lcm)
-                            .unwrap()
-                            .to_string();
+                            .map(|presses| Periodic::new(presses, presses))
+                            .collect();
+                        return align(&inputs)
+                            .map(|together| together.offset.into())
+                            .ok_or_else(|| PuzzleError::new(DAY, "rx never gets a low pulse"));
                     }
                 }
 
@@ -75,71 +145,8 @@ impl Puzzle for Day {
     }
 }
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
-
-    fn parse_modules(&self) -> HashMap<String, Module> {
-        let mut input_map: HashMap<String, Vec<String>> = HashMap::new();
-        let mut modules: HashMap<String, Module> = HashMap::new();
-        for line in self.input.lines() {
-            let mut parts = line.split(" -> ");
-            let full_name = parts.next().unwrap();
-            let name = if full_name.starts_with('%') || full_name.starts_with('&') {
-                full_name[1..].to_string()
-            } else {
-                full_name.to_string()
-            };
-            let outputs = parts
-                .next()
-                .unwrap()
-                .split(", ")
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>();
-            for output in &outputs {
-                input_map
-                    .entry(output.clone())
-                    .or_default()
-                    .push(name.clone());
-            }
-            if full_name.starts_with('%') {
-                modules.insert(
-                    name.clone(),
-                    Module::FlipFlop {
-                        name,
-                        enabled: false,
-                        outputs,
-                    },
-                );
-            } else if full_name.starts_with('&') {
-                modules.insert(
-                    name.clone(),
-                    Module::Conjunction {
-                        name,
-                        memory: HashMap::new(),
-                        outputs,
-                    },
-                );
-            } else {
-                modules.insert(name.clone(), Module::Broadcast { name, outputs });
-            };
-        }
-        for (name, module) in modules.iter_mut() {
-            if let Module::Conjunction { memory, .. } = module {
-                for input in input_map.get(name).unwrap() {
-                    memory.insert(input.clone(), Pulse::Low);
-                }
-            }
-        }
-        modules
-    }
-}
-
 #[derive(PartialEq, Copy, Clone)]
-enum Pulse {
+pub enum Pulse {
     Low,
     High,
 }
@@ -150,7 +157,8 @@ struct Message {
     pulse: Pulse,
 }
 
-enum Module {
+#[derive(Clone)]
+pub enum Module {
     FlipFlop {
         name: String,
         enabled: bool,
@@ -236,8 +244,8 @@ mod tests {
             %b -> c\n\
             %c -> inv\n\
             &inv -> a";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "32000000");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(32000000));
     }
 
     #[test]
@@ -247,21 +255,32 @@ mod tests {
             &inv -> b\n\
             %b -> con\n\
             &con -> output";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "11687500");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(11687500));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/20")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "825167435");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/20")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(825167435));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/20")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "225514321828633");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/20")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(225514321828633));
+    }
+
+    #[test]
+    fn test_missing_rx() {
+        let puzzle = Day::create("broadcaster -> a\n%a -> b\n&b -> output").unwrap();
+        assert_eq!(
+            puzzle.solve_part_2().unwrap_err().to_string(),
+            "Day 20: no module sends pulses to 'rx'"
+        );
     }
 }
@@ -1,56 +1,52 @@
-use crate::puzzle::Puzzle;
+use crate::graph::{Graph, NodeId};
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use lazy_regex::regex_captures;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 22;
+
+pub struct Day;
+
+impl Solver for Day {
+    type Model = BlockGraph;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let blocks = self.parse_blocks();
+    fn parse(text: &str) -> Result<BlockGraph, PuzzleError> {
+        let blocks = parse_blocks(Input::new(DAY, text))?;
         let settled_blocks = settle_blocks(blocks);
-        let graph = BlockGraph::new(settled_blocks);
-        graph.count_safe_to_disintegrate().to_string()
+        Ok(BlockGraph::new(settled_blocks))
     }
 
-    fn solve_part_2(&self) -> String {
-        let blocks = self.parse_blocks();
-        let settled_blocks = settle_blocks(blocks);
-        let graph = BlockGraph::new(settled_blocks);
-        graph.count_falling_blocks().to_string()
+    fn solve_part_1(graph: &BlockGraph) -> Result<Answer, PuzzleError> {
+        Ok(graph.count_safe_to_disintegrate().into())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_2(graph: &BlockGraph) -> Result<Answer, PuzzleError> {
+        Ok(graph.count_falling_blocks().into())
     }
+}
 
-    fn parse_blocks(&self) -> Vec<Block> {
-        self.input
-            .lines()
-            .map(|line| {
-                let (_, x0, y0, z0, x1, y1, z1) =
-                    regex_captures!(r"(\d+),(\d+),(\d+)~(\d+),(\d+),(\d+)", line).unwrap();
-                let x0 = x0.parse::<i32>().unwrap();
-                let y0 = y0.parse::<i32>().unwrap();
-                let z0 = z0.parse::<i32>().unwrap();
-                let x1 = x1.parse::<i32>().unwrap();
-                let y1 = y1.parse::<i32>().unwrap();
-                let z1 = z1.parse::<i32>().unwrap();
-                Block {
-                    x_min: x0.min(x1),
-                    x_max: x0.max(x1),
-                    y_min: y0.min(y1),
-                    y_max: y0.max(y1),
-                    z_min: z0.min(z1),
-                    z_max: z0.max(z1),
-                }
+fn parse_blocks(input: Input) -> Result<Vec<Block>, PuzzleError> {
+    input
+        .lines()
+        .map(|line| {
+            let (_, x0, y0, z0, x1, y1, z1) =
+                regex_captures!(r"(\d+),(\d+),(\d+)~(\d+),(\d+),(\d+)", line)
+                    .ok_or_else(|| input.error(line, "expected 'x,y,z~x,y,z'"))?;
+            let x0 = input.parse::<i32>(x0)?;
+            let y0 = input.parse::<i32>(y0)?;
+            let z0 = input.parse::<i32>(z0)?;
+            let x1 = input.parse::<i32>(x1)?;
+            let y1 = input.parse::<i32>(y1)?;
+            let z1 = input.parse::<i32>(z1)?;
+            Ok(Block {
+                x_min: x0.min(x1),
+                x_max: x0.max(x1),
+                y_min: y0.min(y1),
+                y_max: y0.max(y1),
+                z_min: z0.min(z1),
+                z_max: z0.max(z1),
             })
-            .collect()
-    }
+        })
+        .collect()
 }
 
 fn settle_blocks(mut blocks: Vec<Block>) -> Vec<Block> {
@@ -97,58 +93,58 @@ impl Block {
     }
 }
 
-struct BlockGraph {
-    supports: Vec<Vec<usize>>,
-    supported_by_counts: Vec<usize>,
+/// Which blocks rest directly on which, with the ground as one more node under the lowest blocks.
+pub struct BlockGraph {
+    supports: Graph<usize>,
+    ground: NodeId,
 }
 
 impl BlockGraph {
     fn new(blocks: Vec<Block>) -> BlockGraph {
         let n = blocks.len();
-        let mut supports = vec![Vec::new(); n];
-        let mut supported_by_counts = vec![0; n];
-
+        let mut supports = Graph::new();
+        // Blocks are identified by their index, and the ground by the index after the last.
+        let ground = supports.node(&n);
+        let ids: Vec<NodeId> = (0..n).map(|i| supports.node(&i)).collect();
         for i in 0..n {
+            if blocks[i].z_min == 1 {
+                supports.add_edge(ground, ids[i], ());
+            }
             for j in i + 1..n {
                 if blocks[i].supports(&blocks[j]) {
-                    supports[i].push(j);
-                    supported_by_counts[j] += 1;
+                    supports.add_edge(ids[i], ids[j], ());
                 }
             }
         }
+        BlockGraph { supports, ground }
+    }
 
-        BlockGraph {
-            supports,
-            supported_by_counts,
-        }
+    fn blocks(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.supports.nodes().filter(|&node| node != self.ground)
     }
 
-    fn count_safe_to_disintegrate(&self) -> usize {
-        self.supports
-            .iter()
-            .filter(|supportees| supportees.iter().all(|&j| self.supported_by_counts[j] > 1))
+    pub fn count_safe_to_disintegrate(&self) -> usize {
+        self.blocks()
+            .filter(|&block| {
+                self.supports
+                    .successors(block)
+                    .all(|(above, _)| self.supports.in_degree(above) > 1)
+            })
             .count()
     }
 
-    fn count_falling_blocks(&self) -> usize {
-        let mut sum = 0;
-        let n = self.supports.len();
-        for i in 0..n {
-            let mut count = 0;
-            let mut supported_by_counts = self.supported_by_counts.clone();
-            let mut queue = vec![i];
-            while let Some(i) = queue.pop() {
-                count += 1;
-                for j in &self.supports[i] {
-                    supported_by_counts[*j] -= 1;
-                    if supported_by_counts[*j] == 0 {
-                        queue.push(*j);
-                    }
-                }
-            }
-            sum += count - 1;
-        }
-        sum
+    /// Returns the sum over all blocks of how many other blocks would fall if it were removed. A
+    /// block falls when another is removed exactly if every chain of supports from the ground
+    /// to it passes through the other, that is if the other dominates it.
+    pub fn count_falling_blocks(&self) -> usize {
+        let dominators = self.supports.dominators(self.ground);
+        self.blocks()
+            .map(|block| {
+                dominators
+                    .strict_dominators(block)
+                    .map_or(0, |dominators| dominators.count() - 1)
+            })
+            .sum()
     }
 }
 
@@ -166,15 +162,16 @@ mod tests {
             2,0,5~2,2,5\n\
             0,1,6~2,1,6\n\
             1,1,8~1,1,9";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "5");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(5));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/22")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "432");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/22")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(432));
     }
 
     #[test]
@@ -186,14 +183,15 @@ mod tests {
             2,0,5~2,2,5\n\
             0,1,6~2,1,6\n\
             1,1,8~1,1,9";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "7");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(7));
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/22")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "63166");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/22")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(63166));
     }
 }
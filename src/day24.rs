@@ -1,63 +1,57 @@
-use crate::puzzle::Puzzle;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use itertools::Itertools;
 use lazy_regex::regex;
 use rayon::prelude::*;
 use std::ops::{Add, Mul};
 use z3::ast::{Ast, Int, Real};
-use z3::{Context, Solver};
+use z3::Context;
 
-pub struct Day {
-    input: String,
-}
-
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let hailstones = self.parse_hailstones();
-        count_intersections::<200_000_000_000_000, 400_000_000_000_000>(&hailstones).to_string()
-    }
+const DAY: u8 = 24;
 
-    fn solve_part_2(&self) -> String {
-        let hailstones = self.parse_hailstones();
-        let ctx = &Context::new(&z3::Config::default());
-        let hailstone = find_missing_hailstone(ctx, &hailstones);
-        (hailstone.0 + hailstone.1 + hailstone.2).to_string()
-    }
-}
+pub struct Day;
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
-    }
+impl Solver for Day {
+    type Model = Vec<Hailstone>;
 
-    fn parse_hailstones(&self) -> Vec<Hailstone> {
-        self.input
+    fn parse(text: &str) -> Result<Vec<Hailstone>, PuzzleError> {
+        let input = Input::new(DAY, text);
+        input
             .lines()
             .map(|line| {
-                let mut cap = regex!(r"-?\d+").captures_iter(line);
-                let px = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                let py = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                let pz = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                let vx = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                let vy = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                let vz = cap.next().unwrap()[0].parse::<i64>().unwrap();
-                Hailstone::new(px, py, pz, vx, vy, vz)
+                let numbers = regex!(r"-?\d+")
+                    .find_iter(line)
+                    .map(|m| input.parse::<i64>(m.as_str()))
+                    .collect::<Result<Vec<i64>, PuzzleError>>()?;
+                let [px, py, pz, vx, vy, vz] = numbers[..] else {
+                    return Err(input.error(line, "expected 'px, py, pz @ vx, vy, vz'"));
+                };
+                Ok(Hailstone::new(px, py, pz, vx, vy, vz))
             })
             .collect()
     }
+
+    fn solve_part_1(hailstones: &Vec<Hailstone>) -> Result<Answer, PuzzleError> {
+        Ok(count_intersections::<200_000_000_000_000, 400_000_000_000_000>(hailstones).into())
+    }
+
+    fn solve_part_2(hailstones: &Vec<Hailstone>) -> Result<Answer, PuzzleError> {
+        let ctx = &Context::new(&z3::Config::default());
+        let hailstone = find_missing_hailstone(ctx, hailstones)
+            .ok_or_else(|| PuzzleError::new(DAY, "no rock can be thrown to hit every hailstone"))?;
+        Ok((hailstone.0 + hailstone.1 + hailstone.2).into())
+    }
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
-struct Vec3(i64, i64, i64);
+pub struct Vec3(pub i64, pub i64, pub i64);
 #[derive(PartialEq, Clone, Copy, Debug)]
-struct Hailstone {
-    pos: Vec3,
-    vel: Vec3,
+pub struct Hailstone {
+    pub pos: Vec3,
+    pub vel: Vec3,
 }
 
 impl Hailstone {
-    fn new(px: i64, py: i64, pz: i64, vx: i64, vy: i64, vz: i64) -> Self {
+    pub fn new(px: i64, py: i64, pz: i64, vx: i64, vy: i64, vz: i64) -> Self {
         Self {
             pos: Vec3(px, py, pz),
             vel: Vec3(vx, vy, vz),
@@ -65,7 +59,7 @@ impl Hailstone {
     }
 }
 
-fn count_intersections<const MIN: i64, const MAX: i64>(stones: &[Hailstone]) -> usize {
+pub fn count_intersections<const MIN: i64, const MAX: i64>(stones: &[Hailstone]) -> usize {
     stones
         .iter()
         .tuple_combinations()
@@ -103,8 +97,8 @@ fn intersects<const MIN: i64, const MAX: i64>(stone1: &Hailstone, stone2: &Hails
         && y <= MAX as f64
 }
 
-fn find_missing_hailstone(ctx: &Context, stones: &[Hailstone]) -> Vec3 {
-    let solver = Solver::new(ctx);
+pub fn find_missing_hailstone(ctx: &Context, stones: &[Hailstone]) -> Option<Vec3> {
+    let solver = z3::Solver::new(ctx);
     let px1 = Real::new_const(ctx, "px");
     let py1 = Real::new_const(ctx, "py");
     let pz1 = Real::new_const(ctx, "pz");
@@ -131,11 +125,11 @@ fn find_missing_hailstone(ctx: &Context, stones: &[Hailstone]) -> Vec3 {
         solver.assert(&t.ge(&Real::from_int(&Int::from_i64(ctx, 0))));
     }
     solver.check();
-    let model = solver.get_model().unwrap();
-    let x = model.eval(&px1, false).unwrap().as_real().unwrap().0;
-    let y = model.eval(&py1, false).unwrap().as_real().unwrap().0;
-    let z = model.eval(&pz1, false).unwrap().as_real().unwrap().0;
-    Vec3(x, y, z)
+    let model = solver.get_model()?;
+    let x = model.eval(&px1, false)?.as_real()?.0;
+    let y = model.eval(&py1, false)?.as_real()?.0;
+    let z = model.eval(&pz1, false)?.as_real()?.0;
+    Some(Vec3(x, y, z))
 }
 
 #[cfg(test)]
@@ -158,9 +152,10 @@ mod tests {
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/24")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "18184");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/24")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(18184));
     }
 
     #[test]
@@ -173,13 +168,17 @@ mod tests {
             Hailstone::new(20, 19, 15, 1, -5, -3),
         ];
         let ctx = &Context::new(&Config::default());
-        assert_eq!(find_missing_hailstone(ctx, &hailstones), Vec3(24, 13, 10));
+        assert_eq!(
+            find_missing_hailstone(ctx, &hailstones),
+            Some(Vec3(24, 13, 10))
+        );
     }
 
     #[test]
     fn test_solve_part_2() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/24")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_2(), "557789988450159");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/24")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::Int(557789988450159));
     }
 }
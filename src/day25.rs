@@ -1,47 +1,47 @@
-use crate::puzzle::Puzzle;
+use crate::graph::Graph;
+use crate::puzzle::{Answer, Input, PuzzleError, Solver};
 use petgraph::graph::UnGraph;
+use petgraph::Undirected;
 use rustworkx_core::connectivity::stoer_wagner_min_cut;
-use rustworkx_core::Result;
-use std::collections::HashMap;
 
-pub struct Day {
-    input: String,
-}
+const DAY: u8 = 25;
+
+pub struct Day;
 
-impl Puzzle for Day {
-    fn solve_part_1(&self) -> String {
-        let mut graph = UnGraph::new_undirected();
-        let mut nodes = HashMap::new();
-        for line in self.input.lines() {
-            let mut parts = line.split(':');
-            let node = parts.next().unwrap();
-            let edges = parts.next().unwrap().split_whitespace();
-            let node_index = *nodes
-                .entry(node)
-                .or_insert_with(|| graph.add_node(node.to_string()));
-            for edge in edges {
-                let edge_index = *nodes
-                    .entry(edge)
-                    .or_insert_with(|| graph.add_node(edge.to_string()));
-                graph.add_edge(node_index, edge_index, 1);
-                graph.add_edge(edge_index, node_index, 1);
+impl Solver for Day {
+    /// The components, connected by their wires.
+    type Model = UnGraph<String, ()>;
+
+    fn parse(text: &str) -> Result<Self::Model, PuzzleError> {
+        let input = Input::new(DAY, text);
+        let mut graph = Graph::new();
+        for line in input.lines() {
+            let (node, edges) = line
+                .split_once(':')
+                .ok_or_else(|| input.error(line, "expected 'component: components'"))?;
+            let node = graph.node(node);
+            for edge in edges.split_whitespace() {
+                let edge = graph.node(edge);
+                graph.add_edge(node, edge, ());
             }
         }
-        let min_cut: Result<Option<(usize, Vec<_>)>> = stoer_wagner_min_cut(&graph, |_| Ok(1));
-        let partition_size = min_cut.unwrap().unwrap().1.len();
-        (partition_size * (graph.node_count() - partition_size)).to_string()
+        Ok(graph.to_petgraph::<Undirected>())
     }
 
-    fn solve_part_2(&self) -> String {
-        "Day 25 has no part 2".to_string()
+    fn solve_part_1(graph: &Self::Model) -> Result<Answer, PuzzleError> {
+        let min_cut: rustworkx_core::Result<Option<(usize, Vec<_>)>> =
+            stoer_wagner_min_cut(graph, |_| Ok(1));
+        let partition_size = min_cut
+            .ok()
+            .flatten()
+            .ok_or_else(|| PuzzleError::new(DAY, "the components cannot be split in two"))?
+            .1
+            .len();
+        Ok((partition_size * (graph.node_count() - partition_size)).into())
     }
-}
 
-impl Day {
-    pub fn create(input: &str) -> Box<dyn Puzzle> {
-        Box::new(Day {
-            input: input.to_string(),
-        })
+    fn solve_part_2(_: &Self::Model) -> Result<Answer, PuzzleError> {
+        Ok(Answer::NotApplicable)
     }
 }
 
@@ -65,14 +65,21 @@ mod tests {
             lsr: lhk\n\
             rzs: qnr cmg lsr rsh\n\
             frs: qnr lhk lsr";
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "54");
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(54));
     }
 
     #[test]
     fn test_solve_part_1() {
-        let input = std::fs::read_to_string(PathBuf::from("resources/tests/25")).unwrap();
-        let puzzle = Day::create(&input);
-        assert_eq!(puzzle.solve_part_1(), "495607");
+        let input =
+            crate::secure_store::read_input_file(PathBuf::from("resources/tests/25")).unwrap();
+        let puzzle = Day::create(&input).unwrap();
+        assert_eq!(puzzle.solve_part_1().unwrap(), Answer::Int(495607));
+    }
+
+    #[test]
+    fn test_part_2_not_applicable() {
+        let puzzle = Day::create("a: b").unwrap();
+        assert_eq!(puzzle.solve_part_2().unwrap(), Answer::NotApplicable);
     }
 }
@@ -0,0 +1,265 @@
+use scraper::{ElementRef, Html, Selector};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The directory where example fixtures are kept, with one subdirectory per year and day (such as
+/// `2023/01`).
+pub const FIXTURE_DIR: &str = "resources/examples";
+
+/// An example from a puzzle description: an input and the answer expected for one part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Example {
+    pub part: u8,
+    pub input: String,
+    pub answer: String,
+}
+
+/// An example stored as a fixture file, named after its file (such as `part1` or `part2-2`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    pub year: u16,
+    pub day: u8,
+    pub name: String,
+    pub example: Example,
+}
+
+/// Extracts the examples from a puzzle page. Each part's example input is the first `<pre><code>`
+/// block in its description (part 2 reuses part 1's input if it has none), and its answer is the
+/// last emphasised `<code>` outside of a block. These are heuristics, so the extracted examples
+/// should be checked before they are kept.
+pub fn extract(html: &str) -> Vec<Example> {
+    let document = Html::parse_document(html);
+    let articles = Selector::parse("article.day-desc").unwrap();
+    let blocks = Selector::parse("pre > code").unwrap();
+    let emphasised = Selector::parse("code > em, em > code").unwrap();
+    let mut examples = Vec::new();
+    let mut input = None;
+    for (i, article) in document.select(&articles).enumerate() {
+        if let Some(block) = article.select(&blocks).next() {
+            input = Some(block.text().collect::<String>());
+        }
+        let answer = article
+            .select(&emphasised)
+            .filter(|element| !in_block(element))
+            .last()
+            .map(|element| element.text().collect::<String>());
+        if let (Some(input), Some(answer)) = (&input, answer) {
+            examples.push(Example {
+                part: i as u8 + 1,
+                input: input.clone(),
+                answer: answer.trim().to_string(),
+            });
+        }
+    }
+    examples
+}
+
+fn in_block(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .any(|node| node.value().as_element().is_some_and(|e| e.name() == "pre"))
+}
+
+/// Writes the examples of a day as fixtures named after their part, returning the paths of the
+/// inputs written. Existing fixtures are never overwritten, so corrected ones are kept.
+pub fn write_fixtures(
+    dir: &Path,
+    year: u16,
+    day: u8,
+    examples: &[Example],
+) -> io::Result<Vec<PathBuf>> {
+    let day_dir = dir.join(year.to_string()).join(format!("{:02}", day));
+    let mut written = Vec::new();
+    for example in examples {
+        let input_path = day_dir.join(format!("part{}.input", example.part));
+        if input_path.exists() {
+            continue;
+        }
+        fs::create_dir_all(&day_dir)?;
+        fs::write(&input_path, &example.input)?;
+        fs::write(input_path.with_extension("answer"), &example.answer)?;
+        written.push(input_path);
+    }
+    Ok(written)
+}
+
+/// Loads every fixture in the directory, in year, day and name order. Each fixture is a `.input`
+/// file whose name starts with its part (such as `part2-2.input`) next to a matching `.answer`
+/// file.
+pub fn load_fixtures(dir: &Path) -> io::Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for (year, year_dir) in numbered_dirs::<u16>(dir)? {
+        for (day, day_dir) in numbered_dirs::<u8>(&year_dir)? {
+            load_day_fixtures(year, day, &day_dir, &mut fixtures)?;
+        }
+    }
+    fixtures.sort_by(|a, b| (a.year, a.day, &a.name).cmp(&(b.year, b.day, &b.name)));
+    Ok(fixtures)
+}
+
+/// Returns the subdirectories of the directory that are named after a number, if it exists.
+fn numbered_dirs<T: std::str::FromStr>(dir: &Path) -> io::Result<Vec<(T, PathBuf)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(number) = file_name(&path).and_then(|name| name.parse::<T>().ok()) {
+            dirs.push((number, path));
+        }
+    }
+    Ok(dirs)
+}
+
+/// Loads the fixtures of one day into the list.
+fn load_day_fixtures(
+    year: u16,
+    day: u8,
+    day_dir: &Path,
+    fixtures: &mut Vec<Fixture>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(day_dir)? {
+        let input_path = entry?.path();
+        if input_path
+            .extension()
+            .is_none_or(|extension| extension != "input")
+        {
+            continue;
+        }
+        let name = input_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let part = match name
+            .strip_prefix("part")
+            .and_then(|rest| rest.chars().next())
+        {
+            Some('1') => 1,
+            Some('2') => 2,
+            _ => return Err(invalid_fixture(&input_path, "name must start with a part")),
+        };
+        let answer_path = input_path.with_extension("answer");
+        let answer = fs::read_to_string(&answer_path)
+            .map_err(|_| invalid_fixture(&input_path, "missing .answer file"))?;
+        fixtures.push(Fixture {
+            year,
+            day,
+            name,
+            example: Example {
+                part,
+                input: fs::read_to_string(&input_path)?,
+                answer: answer.trim().to_string(),
+            },
+        });
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()
+}
+
+fn invalid_fixture(path: &Path, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use tempfile::TempDir;
+
+    const PAGE: &str = "<html><body><main>\
+        <article class=\"day-desc\"><h2>--- Day 9: Mirage Maintenance ---</h2>\
+        <p>For example:</p><pre><code>0 3 6 9 12 15\n1 3 6 10 15 21\n</code></pre>\
+        <p>Highlighted: <pre><code>0   3   <em>6</em></code></pre></p>\
+        <p>The next value is <code><em>18</em></code>, so the sum is <code><em>114</em></code>.</p>\
+        </article><p>Your puzzle answer was <code>1684566095</code>.</p>\
+        <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+        <p>The sum of the previous values is <em><code>2</code></em>.</p></article>\
+        </main></body></html>";
+
+    #[test]
+    fn extract_finds_input_and_emphasised_answer_per_part() {
+        let input = "0 3 6 9 12 15\n1 3 6 10 15 21\n".to_string();
+        assert_eq!(
+            extract(PAGE),
+            vec![
+                Example {
+                    part: 1,
+                    input: input.clone(),
+                    answer: "114".to_string(),
+                },
+                Example {
+                    part: 2,
+                    input,
+                    answer: "2".to_string(),
+                },
+            ]
+        );
+        assert!(extract("<html><body>No puzzle</body></html>").is_empty());
+    }
+
+    #[test]
+    fn fixtures_round_trip_without_overwriting() {
+        let dir = TempDir::new().unwrap();
+        let examples = extract(PAGE);
+        let written = write_fixtures(dir.path(), 2023, 9, &examples).unwrap();
+        assert_eq!(written.len(), 2);
+        fs::write(dir.path().join("2023/09/part2.answer"), "3\n").unwrap();
+        assert!(write_fixtures(dir.path(), 2023, 9, &examples)
+            .unwrap()
+            .is_empty());
+
+        let fixtures = load_fixtures(dir.path()).unwrap();
+        let names: Vec<&str> = fixtures.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["part1", "part2"]);
+        assert_eq!(fixtures[0].example, examples[0]);
+        assert_eq!(fixtures[1].example.answer, "3");
+        assert!(load_fixtures(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn load_fixtures_requires_answer() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("2023/01")).unwrap();
+        fs::write(dir.path().join("2023/01/part1.input"), "1abc2").unwrap();
+        assert!(load_fixtures(dir.path()).is_err());
+    }
+
+    /// Solves every example fixture with its registered puzzle.
+    #[test]
+    fn registered_puzzles_solve_example_fixtures() {
+        let fixtures = load_fixtures(Path::new(FIXTURE_DIR)).unwrap();
+        assert!(!fixtures.is_empty());
+        let mut failures = Vec::new();
+        for fixture in &fixtures {
+            let entry = registry::get(fixture.year, fixture.day).unwrap();
+            let example = &fixture.example;
+            let answer = (entry.create)(&example.input).and_then(|puzzle| match example.part {
+                1 => puzzle.solve_part_1(),
+                _ => puzzle.solve_part_2(),
+            });
+            match answer {
+                Ok(answer) if answer.to_string() == example.answer => {}
+                Ok(answer) => failures.push(format!(
+                    "{} day {:02} {}: expected {}, got {}",
+                    fixture.year, fixture.day, fixture.name, example.answer, answer
+                )),
+                Err(e) => failures.push(format!(
+                    "{} day {:02} {}: {}",
+                    fixture.year, fixture.day, fixture.name, e
+                )),
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
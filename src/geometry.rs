@@ -0,0 +1,298 @@
+use crate::grid::{Direction, Point};
+use std::fmt::Display;
+
+/// Why a polygon could not be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonError {
+    /// The walk does not end where it started.
+    NotClosed,
+    /// A coordinate or a measurement does not fit in its integer type.
+    Overflow,
+}
+
+impl Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PolygonError::NotClosed => write!(f, "the walk does not return to where it started"),
+            PolygonError::Overflow => write!(f, "the polygon is too large"),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+/// The direction a polygon's vertices go around it, as seen on a grid whose rows increase
+/// downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    Counterclockwise,
+    /// The polygon encloses no area.
+    Degenerate,
+}
+
+/// A polygon whose vertices lie on the integer lattice. Its measurements are exact, and are
+/// computed once when it is built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+    /// Twice the signed area, positive if the vertices go clockwise (the shoelace formula).
+    signed_double_area: i128,
+    boundary_points: u128,
+}
+
+impl Polygon {
+    /// Builds the polygon traced by a closed walk of steps in the given directions, starting from
+    /// the origin. Consecutive steps in the same direction are merged into one edge.
+    pub fn from_steps(
+        steps: impl IntoIterator<Item = (Direction, u64)>,
+    ) -> Result<Self, PolygonError> {
+        let mut vertices = vec![Point::default()];
+        let mut position = Point::default();
+        let mut previous = None;
+        for (direction, length) in steps {
+            if length == 0 {
+                continue;
+            }
+            let length = isize::try_from(length).map_err(|_| PolygonError::Overflow)?;
+            let offset = direction.offset();
+            position = Point::new(
+                offset
+                    .row
+                    .checked_mul(length)
+                    .and_then(|row| row.checked_add(position.row))
+                    .ok_or(PolygonError::Overflow)?,
+                offset
+                    .col
+                    .checked_mul(length)
+                    .and_then(|col| col.checked_add(position.col))
+                    .ok_or(PolygonError::Overflow)?,
+            );
+            if previous == Some(direction) {
+                *vertices.last_mut().unwrap() = position;
+            } else {
+                vertices.push(position);
+            }
+            previous = Some(direction);
+        }
+        if vertices.pop() != Some(Point::default()) {
+            return Err(PolygonError::NotClosed);
+        }
+        // The first and last edges are merged too if they run in the same direction.
+        if vertices.len() > 2
+            && collinear_and_same_way(vertices[vertices.len() - 1], Point::default(), vertices[1])
+        {
+            vertices.remove(0);
+        }
+        Self::from_vertices(vertices)
+    }
+
+    /// Builds the polygon with the given vertices, in order. The last vertex is joined to the
+    /// first.
+    pub fn from_vertices(vertices: Vec<Point>) -> Result<Self, PolygonError> {
+        let mut signed_double_area = 0i128;
+        let mut boundary_points = 0u128;
+        for (i, &a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % vertices.len()];
+            let term = a.col as i128 * b.row as i128 - b.col as i128 * a.row as i128;
+            signed_double_area = signed_double_area
+                .checked_add(term)
+                .ok_or(PolygonError::Overflow)?;
+            // An edge passes through one lattice point per step of its reduced direction, counting
+            // one of its ends.
+            let (rows, cols) = (a.row.abs_diff(b.row), a.col.abs_diff(b.col));
+            boundary_points = boundary_points
+                .checked_add(gcd(rows, cols) as u128)
+                .ok_or(PolygonError::Overflow)?;
+        }
+        Ok(Self {
+            vertices,
+            signed_double_area,
+            boundary_points,
+        })
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns twice the area enclosed, which is always an integer.
+    pub fn double_area(&self) -> u128 {
+        self.signed_double_area.unsigned_abs()
+    }
+
+    /// Returns the number of lattice points on the edges.
+    pub fn boundary_points(&self) -> u128 {
+        self.boundary_points
+    }
+
+    /// Returns the number of lattice points strictly inside the polygon, by Pick's theorem.
+    /// Only meaningful if the polygon does not intersect itself.
+    pub fn interior_points(&self) -> u128 {
+        // A = I + B / 2 - 1, so I = (2A - B + 2) / 2.
+        (self.double_area() + 2).saturating_sub(self.boundary_points) / 2
+    }
+
+    /// Returns the number of lattice points inside the polygon or on its edges. Only meaningful
+    /// if the polygon does not intersect itself.
+    pub fn enclosed_points(&self) -> u128 {
+        self.interior_points() + self.boundary_points
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        match self.signed_double_area.signum() {
+            1 => Orientation::Clockwise,
+            -1 => Orientation::Counterclockwise,
+            _ => Orientation::Degenerate,
+        }
+    }
+
+    /// Returns whether any two edges touch other than adjacent edges at their shared vertex. This
+    /// compares every pair of edges, so takes time quadratic in the number of vertices.
+    pub fn is_self_intersecting(&self) -> bool {
+        let n = self.vertices.len();
+        let edge = |i: usize| (self.vertices[i], self.vertices[(i + 1) % n]);
+        (0..n).any(|i| {
+            let (a, b) = edge(i);
+            let (_, c) = edge((i + 1) % n);
+            // An edge that doubles back over the previous one.
+            if n > 1 && cross(a, b, c) == 0 && dot(b - a, c - b) < 0 {
+                return true;
+            }
+            (i + 2..n).filter(|&j| (j + 1) % n != i).any(|j| {
+                let (c, d) = edge(j);
+                segments_intersect(a, b, c, d)
+            })
+        })
+    }
+}
+
+/// Returns whether b lies on the line from a to c, between them.
+fn collinear_and_same_way(a: Point, b: Point, c: Point) -> bool {
+    cross(a, b, c) == 0 && dot(b - a, c - b) > 0
+}
+
+/// Returns the cross product of the vectors from a to b and from a to c, which is positive if c
+/// is clockwise of the line from a to b (on a grid whose rows increase downwards).
+fn cross(a: Point, b: Point, c: Point) -> i128 {
+    let (ab, ac) = (b - a, c - a);
+    ab.col as i128 * ac.row as i128 - ab.row as i128 * ac.col as i128
+}
+
+fn dot(a: Point, b: Point) -> i128 {
+    a.row as i128 * b.row as i128 + a.col as i128 * b.col as i128
+}
+
+/// Returns whether the closed segments from a to b and from c to d share any point.
+fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let on_segment = |p: Point, q: Point, r: Point| {
+        (p.row.min(q.row)..=p.row.max(q.row)).contains(&r.row)
+            && (p.col.min(q.col)..=p.col.max(q.col)).contains(&r.col)
+    };
+    let (d1, d2) = (cross(c, d, a).signum(), cross(c, d, b).signum());
+    let (d3, d4) = (cross(a, b, c).signum(), cross(a, b, d).signum());
+    (d1 * d2 < 0 && d3 * d4 < 0)
+        || (d1 == 0 && on_segment(c, d, a))
+        || (d2 == 0 && on_segment(c, d, b))
+        || (d3 == 0 && on_segment(a, b, c))
+        || (d4 == 0 && on_segment(a, b, d))
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::{East, North, South, West};
+
+    #[test]
+    fn square_measurements() {
+        let square = Polygon::from_steps([(East, 2), (South, 2), (West, 2), (North, 2)]).unwrap();
+        assert_eq!(square.vertices().len(), 4);
+        assert_eq!(square.double_area(), 8);
+        assert_eq!(square.boundary_points(), 8);
+        assert_eq!(square.interior_points(), 1);
+        assert_eq!(square.enclosed_points(), 9);
+        assert_eq!(square.orientation(), Orientation::Clockwise);
+        assert!(!square.is_self_intersecting());
+
+        let reversed = Polygon::from_steps([(South, 2), (East, 2), (North, 2), (West, 2)]).unwrap();
+        assert_eq!(reversed.orientation(), Orientation::Counterclockwise);
+        assert_eq!(reversed.enclosed_points(), 9);
+    }
+
+    #[test]
+    fn unit_steps_are_merged_into_edges() {
+        let unit_steps = |steps: &[Direction]| {
+            Polygon::from_steps(steps.iter().map(|&direction| (direction, 1))).unwrap()
+        };
+        let polygon = unit_steps(&[East, East, South, West, West, North]);
+        assert_eq!(polygon.vertices().len(), 4);
+        assert_eq!(polygon.double_area(), 4);
+        // Starting mid-way along an edge still gives the same corners.
+        let polygon = unit_steps(&[East, South, West, West, North, East]);
+        assert_eq!(polygon.vertices().len(), 4);
+        assert_eq!(polygon.boundary_points(), 6);
+        assert_eq!(polygon.interior_points(), 0);
+    }
+
+    #[test]
+    fn diagonal_edges_count_lattice_points() {
+        let triangle =
+            Polygon::from_vertices(vec![Point::new(0, 0), Point::new(0, 4), Point::new(4, 0)])
+                .unwrap();
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.double_area(), 16);
+        assert_eq!(triangle.interior_points(), 3);
+    }
+
+    #[test]
+    fn open_or_oversized_walks_are_rejected() {
+        assert_eq!(
+            Polygon::from_steps([(East, 2), (South, 2)]),
+            Err(PolygonError::NotClosed)
+        );
+        assert_eq!(
+            Polygon::from_steps([(East, u64::MAX), (West, u64::MAX)]),
+            Err(PolygonError::Overflow)
+        );
+        assert_eq!(
+            Polygon::from_steps([(East, isize::MAX as u64), (East, 1)]),
+            Err(PolygonError::Overflow)
+        );
+    }
+
+    #[test]
+    fn self_intersections_are_detected() {
+        // A figure of eight, crossing itself at the origin.
+        let eight = Polygon::from_vertices(vec![
+            Point::new(-1, -1),
+            Point::new(1, 1),
+            Point::new(1, -1),
+            Point::new(-1, 1),
+        ])
+        .unwrap();
+        assert!(eight.is_self_intersecting());
+        // Two squares touching at a corner.
+        let touching = Polygon::from_steps([
+            (East, 1),
+            (South, 1),
+            (East, 1),
+            (South, 1),
+            (West, 1),
+            (North, 1),
+            (West, 1),
+            (North, 1),
+        ])
+        .unwrap();
+        assert!(touching.is_self_intersecting());
+        let doubled_back = Polygon::from_steps([(East, 2), (West, 1), (West, 1)]).unwrap();
+        assert!(doubled_back.is_self_intersecting());
+    }
+}
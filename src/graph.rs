@@ -0,0 +1,353 @@
+use petgraph::algo::dominators::{self, Dominators};
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use petgraph::{Directed, Direction, EdgeType};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+/// Identifies a node of a [`Graph`]. IDs stay the same when other nodes are removed.
+pub type NodeId = NodeIndex;
+
+/// A directed graph with weighted edges, whose nodes are identified by keys such as names. Each
+/// key is interned as a [`NodeId`] the first time it is used, so the graph can be walked without
+/// hashing keys.
+#[derive(Debug, Clone)]
+pub struct Graph<K = String, E = ()> {
+    graph: StableDiGraph<K, E>,
+    ids: HashMap<K, NodeId>,
+}
+
+impl<K, E> Default for Graph<K, E> {
+    fn default() -> Self {
+        Self {
+            graph: StableDiGraph::default(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, E> Graph<K, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the ID of the node with the key, adding the node if there is none.
+    pub fn node<Q>(&mut self, key: &Q) -> NodeId
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+    {
+        if let Some(&id) = self.ids.get(key) {
+            return id;
+        }
+        let id = self.graph.add_node(key.to_owned());
+        self.ids.insert(key.to_owned(), id);
+        id
+    }
+
+    /// Returns the ID of the node with the key, if there is one.
+    pub fn id<Q>(&self, key: &Q) -> Option<NodeId>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.get(key).copied()
+    }
+
+    /// Returns the key of a node. Panics if the node is not in the graph.
+    pub fn key(&self, id: NodeId) -> &K {
+        &self.graph[id]
+    }
+
+    /// Removes a node and its edges, returning its key if it was in the graph.
+    pub fn remove_node(&mut self, id: NodeId) -> Option<K> {
+        let key = self.graph.remove_node(id)?;
+        self.ids.remove(&key);
+        Some(key)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.graph.node_indices()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Returns an upper bound on the index of any node, for indexing vectors by node.
+    pub fn node_bound(&self) -> usize {
+        self.graph
+            .node_indices()
+            .map(|id| id.index() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Adds an edge, even if the nodes already have one.
+    pub fn add_edge(&mut self, src: NodeId, dst: NodeId, weight: E) {
+        self.graph.add_edge(src, dst, weight);
+    }
+
+    /// Returns the weight of an edge from one node to another, if there is one.
+    pub fn edge(&self, src: NodeId, dst: NodeId) -> Option<&E> {
+        self.graph.find_edge(src, dst).map(|edge| &self.graph[edge])
+    }
+
+    /// Removes an edge from one node to another, returning its weight if there was one.
+    pub fn remove_edge(&mut self, src: NodeId, dst: NodeId) -> Option<E> {
+        let edge = self.graph.find_edge(src, dst)?;
+        self.graph.remove_edge(edge)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Returns the nodes that a node has edges to, with the weights of the edges.
+    pub fn successors(&self, id: NodeId) -> impl Iterator<Item = (NodeId, &E)> + '_ {
+        self.graph
+            .edges_directed(id, Direction::Outgoing)
+            .map(|edge| (edge.target(), edge.weight()))
+    }
+
+    /// Returns the nodes that have edges to a node, with the weights of the edges.
+    pub fn predecessors(&self, id: NodeId) -> impl Iterator<Item = (NodeId, &E)> + '_ {
+        self.graph
+            .edges_directed(id, Direction::Incoming)
+            .map(|edge| (edge.source(), edge.weight()))
+    }
+
+    pub fn out_degree(&self, id: NodeId) -> usize {
+        self.successors(id).count()
+    }
+
+    pub fn in_degree(&self, id: NodeId) -> usize {
+        self.predecessors(id).count()
+    }
+
+    /// Removes the nodes that only join two others by edges both ways, such as the middle of a
+    /// corridor, replacing them with edges both ways between their neighbors:
+    ///   x <---m---> y <---n---> z
+    /// becomes
+    ///   x <---m+n---> z
+    /// where the weights are combined by `combine`. Nodes for which `keep` is true are left, as
+    /// are nodes whose neighbors are already joined. Returns the number of nodes removed.
+    pub fn contract_degree_2(
+        &mut self,
+        keep: impl Fn(NodeId) -> bool,
+        combine: impl Fn(&E, &E) -> E,
+    ) -> usize {
+        let mut removed = 0;
+        let mut modified = true;
+        while modified {
+            modified = false;
+            for node in self.nodes().collect::<Vec<_>>() {
+                if keep(node) {
+                    continue;
+                }
+                let Some((a, b)) = self.corridor_neighbors(node) else {
+                    continue;
+                };
+                if self.edge(a, b).is_some() || self.edge(b, a).is_some() {
+                    continue;
+                }
+                let forward = combine(self.edge(a, node).unwrap(), self.edge(node, b).unwrap());
+                let backward = combine(self.edge(b, node).unwrap(), self.edge(node, a).unwrap());
+                self.remove_node(node);
+                self.add_edge(a, b, forward);
+                self.add_edge(b, a, backward);
+                removed += 1;
+                modified = true;
+            }
+        }
+        removed
+    }
+
+    /// Returns the two neighbors of a node if it has exactly one edge each way with each of two
+    /// different nodes.
+    fn corridor_neighbors(&self, node: NodeId) -> Option<(NodeId, NodeId)> {
+        let mut outgoing: Vec<NodeId> = self.successors(node).map(|(id, _)| id).collect();
+        let mut incoming: Vec<NodeId> = self.predecessors(node).map(|(id, _)| id).collect();
+        outgoing.sort_unstable();
+        incoming.sort_unstable();
+        match outgoing[..] {
+            [a, b] if a != b && outgoing == incoming => Some((a, b)),
+            _ => None,
+        }
+    }
+
+    /// Returns the dominator tree of the nodes reachable from the root: a node dominates another
+    /// if every path from the root to the other passes through it.
+    pub fn dominators(&self, root: NodeId) -> Dominators<NodeId> {
+        dominators::simple_fast(&self.graph, root)
+    }
+
+    /// Returns the strongly connected components, in reverse topological order: each component
+    /// only has edges to components before it.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        petgraph::algo::tarjan_scc(&self.graph)
+    }
+
+    /// Returns the graph in the DOT language, with each node labelled by its key and each edge by
+    /// `edge_label`, if it gives one.
+    pub fn to_dot(&self, edge_label: impl Fn(&E) -> Option<String>) -> String
+    where
+        K: Display,
+    {
+        let mut dot = String::from("digraph {\n");
+        for id in self.graph.node_indices() {
+            let label = self.graph[id].to_string();
+            writeln!(dot, "    {} [label={:?}]", id.index(), label).unwrap();
+        }
+        for edge in self.graph.edge_references() {
+            let (src, dst) = (edge.source().index(), edge.target().index());
+            match edge_label(edge.weight()) {
+                Some(label) => writeln!(dot, "    {} -> {} [label={:?}]", src, dst, label),
+                None => writeln!(dot, "    {} -> {}", src, dst),
+            }
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Copies the graph into a petgraph graph, either directed or undirected. Nodes are numbered
+    /// from zero in the order of their IDs.
+    pub fn to_petgraph<Ty: EdgeType>(&self) -> petgraph::Graph<K, E, Ty>
+    where
+        E: Clone,
+    {
+        let mut graph = petgraph::Graph::with_capacity(self.node_count(), self.edge_count());
+        let mut indices = HashMap::new();
+        for id in self.graph.node_indices() {
+            indices.insert(id, graph.add_node(self.graph[id].clone()));
+        }
+        for edge in self.graph.edge_references() {
+            graph.add_edge(
+                indices[&edge.source()],
+                indices[&edge.target()],
+                edge.weight().clone(),
+            );
+        }
+        graph
+    }
+}
+
+impl<K: Clone + Eq + Hash, E> From<petgraph::Graph<K, E, Directed>> for Graph<K, E> {
+    /// Builds a graph from a petgraph graph. Nodes with the same key are merged.
+    fn from(graph: petgraph::Graph<K, E, Directed>) -> Self {
+        let mut result = Self::new();
+        let (nodes, edges) = graph.into_nodes_edges();
+        let ids: Vec<NodeId> = nodes
+            .into_iter()
+            .map(|node| result.node(&node.weight))
+            .collect();
+        for edge in edges {
+            result.add_edge(
+                ids[edge.source().index()],
+                ids[edge.target().index()],
+                edge.weight,
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::Undirected;
+
+    /// Builds a graph with an edge of the given weight for each (src, dst, weight).
+    fn build<E: Clone>(edges: &[(&str, &str, E)]) -> Graph<String, E> {
+        let mut graph = Graph::new();
+        for (src, dst, weight) in edges {
+            let (src, dst) = (graph.node(*src), graph.node(*dst));
+            graph.add_edge(src, dst, weight.clone());
+        }
+        graph
+    }
+
+    #[test]
+    fn nodes_are_interned() {
+        let mut graph = build(&[("a", "b", 1), ("b", "c", 2), ("a", "c", 3)]);
+        assert_eq!(graph.node_count(), 3);
+        let (a, c) = (graph.id("a").unwrap(), graph.id("c").unwrap());
+        assert_eq!(graph.node("a"), a);
+        assert_eq!(graph.key(c), "c");
+        assert_eq!(graph.edge(a, c), Some(&3));
+        assert_eq!(graph.edge(c, a), None);
+        assert_eq!((graph.out_degree(a), graph.in_degree(c)), (2, 2));
+
+        assert_eq!(graph.remove_node(a), Some("a".to_string()));
+        assert_eq!(graph.id("a"), None);
+        assert_eq!(graph.in_degree(c), 1);
+        // The remaining nodes keep their IDs.
+        assert_eq!(graph.id("c"), Some(c));
+    }
+
+    #[test]
+    fn corridors_are_contracted() {
+        // A corridor a-b-c-d, with a side room x off c.
+        let corridor = || {
+            let mut graph = Graph::new();
+            for (src, dst, weight) in [("a", "b", 1), ("b", "c", 2), ("c", "d", 3), ("c", "x", 4)] {
+                let (src, dst) = (graph.node(src), graph.node(dst));
+                graph.add_edge(src, dst, weight);
+                graph.add_edge(dst, src, weight);
+            }
+            graph
+        };
+        let mut graph = corridor();
+        assert_eq!(graph.contract_degree_2(|_| false, |m, n| m + n), 1);
+        let (a, c) = (graph.id("a").unwrap(), graph.id("c").unwrap());
+        assert_eq!(graph.id("b"), None);
+        assert_eq!((graph.edge(a, c), graph.edge(c, a)), (Some(&3), Some(&3)));
+        assert_eq!(graph.out_degree(c), 3);
+
+        let mut graph = corridor();
+        let b = graph.id("b").unwrap();
+        assert_eq!(graph.contract_degree_2(|id| id == b, |m, n| m + n), 0);
+        assert_eq!(graph.node_count(), 5);
+    }
+
+    #[test]
+    fn dominators_and_components() {
+        let graph = build(&[
+            ("root", "a", ()),
+            ("root", "b", ()),
+            ("a", "c", ()),
+            ("b", "c", ()),
+            ("c", "d", ()),
+            ("d", "c", ()),
+        ]);
+        let id = |key: &str| graph.id(key).unwrap();
+        let dominators = graph.dominators(id("root"));
+        assert_eq!(dominators.immediate_dominator(id("c")), Some(id("root")));
+        assert_eq!(dominators.immediate_dominator(id("d")), Some(id("c")));
+
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 4);
+        let mut cycle = components[0].clone();
+        cycle.sort();
+        assert_eq!(cycle, vec![id("c"), id("d")]);
+    }
+
+    #[test]
+    fn petgraph_conversion_and_dot() {
+        let graph = build(&[("a", "b", 'L'), ("a", "a", 'R')]);
+        let undirected = graph.to_petgraph::<Undirected>();
+        assert_eq!((undirected.node_count(), undirected.edge_count()), (2, 2));
+        let directed = graph.to_petgraph::<Directed>();
+        let round_trip = Graph::from(directed);
+        let a = round_trip.id("a").unwrap();
+        assert_eq!(round_trip.edge(a, a), Some(&'R'));
+
+        assert_eq!(
+            graph.to_dot(|weight| (*weight == 'L').then(|| weight.to_string())),
+            "digraph {\n    0 [label=\"a\"]\n    1 [label=\"b\"]\n    0 -> 1 [label=\"L\"]\n    0 -> 0\n}\n"
+        );
+    }
+}
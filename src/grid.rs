@@ -0,0 +1,394 @@
+use crate::puzzle::{Input, PuzzleError};
+use std::ops::{Add, AddAssign, Index, IndexMut, Sub};
+
+/// A position on a grid, as a row (increasing downwards) and a column (increasing rightwards).
+/// Points may lie outside a grid, such as when stepping off its edge or on a repeating grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub row: isize,
+    pub col: isize,
+}
+
+impl Point {
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    /// Returns the point one step away in the given direction.
+    pub fn step(self, direction: Direction) -> Self {
+        self + direction.offset()
+    }
+
+    /// Returns the four points that share an edge with this one, in the order of
+    /// [Direction::ALL].
+    pub fn neighbors4(self) -> impl Iterator<Item = Point> {
+        Direction::ALL
+            .into_iter()
+            .map(move |direction| self.step(direction))
+    }
+
+    /// Returns the eight points that share an edge or a corner with this one, clockwise from the
+    /// one above.
+    pub fn neighbors8(self) -> impl Iterator<Item = Point> {
+        [
+            (-1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+        ]
+        .into_iter()
+        .map(move |(row, col)| self + Point::new(row, col))
+    }
+
+    /// Returns the number of orthogonal steps between the two points.
+    pub fn manhattan_distance(self, other: Point) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+/// One of the four orthogonal directions on a grid, with north being up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Returns the change in position of a step in this direction.
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::North => Point::new(-1, 0),
+            Direction::East => Point::new(0, 1),
+            Direction::South => Point::new(1, 0),
+            Direction::West => Point::new(0, -1),
+        }
+    }
+
+    /// Returns the direction a quarter turn clockwise from this one.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Returns the direction a quarter turn counterclockwise from this one.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    /// Returns the opposite direction.
+    pub fn reverse(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    /// Returns whether the direction is north or south.
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+}
+
+/// A rectangular grid of tiles, stored row by row in a flat buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    /// Parses a grid of characters, failing unless the input is a non-empty rectangle of tiles
+    /// found in `tiles`.
+    pub fn parse(input: Input, tiles: &str) -> Result<Self, PuzzleError> {
+        let lines = input.grid(tiles)?;
+        let cols = lines[0].chars().count();
+        Ok(Self::new(
+            lines.len(),
+            cols,
+            lines.iter().flat_map(|line| line.chars()).collect(),
+        ))
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from its cells in row order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not `rows * cols` cells.
+    pub fn new(rows: usize, cols: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            rows * cols,
+            "grid has the wrong number of cells"
+        );
+        Self { rows, cols, cells }
+    }
+
+    /// Creates a grid with every cell set to the value.
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(rows, cols, vec![value; rows * cols])
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns whether the point lies on the grid.
+    pub fn contains(&self, point: Point) -> bool {
+        (0..self.rows as isize).contains(&point.row) && (0..self.cols as isize).contains(&point.col)
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        self.contains(point)
+            .then(|| point.row as usize * self.cols + point.col as usize)
+    }
+
+    /// Returns the tile at the point, or `None` if it lies off the grid.
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|i| &mut self.cells[i])
+    }
+
+    /// Returns the point on the grid that the point corresponds to if the grid repeats forever in
+    /// every direction.
+    pub fn wrap(&self, point: Point) -> Point {
+        Point::new(
+            point.row.rem_euclid(self.rows as isize),
+            point.col.rem_euclid(self.cols as isize),
+        )
+    }
+
+    /// Returns the tile at the point, treating the grid as repeating forever in every direction.
+    pub fn get_wrapping(&self, point: Point) -> &T {
+        &self[self.wrap(point)]
+    }
+
+    /// Returns a row of the grid.
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Returns every point on the grid in row order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let cols = self.cols;
+        (0..self.rows * cols).map(move |i| Point::new((i / cols) as isize, (i % cols) as isize))
+    }
+
+    /// Returns every point on the grid with its tile, in row order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    /// Returns the points on the grid that share an edge with the point.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        point
+            .neighbors4()
+            .filter(move |&neighbor| self.contains(neighbor))
+    }
+
+    /// Returns the points on the grid that share an edge or a corner with the point.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        point
+            .neighbors8()
+            .filter(move |&neighbor| self.contains(neighbor))
+    }
+
+    /// Returns the first point, in row order, whose tile is the value.
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .find(|(_, tile)| *tile == value)
+            .map(|(point, _)| point)
+    }
+
+    /// Returns a grid with the function applied to every tile.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.rows, self.cols, self.cells.iter().map(f).collect())
+    }
+
+    /// Returns the grid mirrored along its main diagonal, so that its rows become columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rearrange(self.cols, self.rows, |row, col| (col, row))
+    }
+
+    /// Returns the grid turned a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rearrange(self.cols, self.rows, |row, col| (self.rows - 1 - col, row))
+    }
+
+    /// Returns the grid turned a quarter turn counterclockwise.
+    pub fn rotate_counterclockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rearrange(self.cols, self.rows, |row, col| (col, self.cols - 1 - row))
+    }
+
+    /// Builds a grid of the given size whose tile at each row and column is this grid's tile at
+    /// the row and column returned by `source`.
+    fn rearrange(
+        &self,
+        rows: usize,
+        cols: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..rows * cols)
+            .map(|i| {
+                let (row, col) = source(i / cols, i % cols);
+                self.cells[row * self.cols + col].clone()
+            })
+            .collect();
+        Grid::new(rows, cols, cells)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    /// Returns the tile at the point, panicking if it lies off the grid.
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is off the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is off the grid", point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::parse(Input::new(1, "abc\ndef\n"), "abcdef").unwrap()
+    }
+
+    fn text(grid: &Grid<char>) -> Vec<String> {
+        (0..grid.rows())
+            .map(|row| grid.row(row).iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn parse_builds_rectangle() {
+        let grid = grid();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[Point::new(1, 2)], 'f');
+        assert_eq!(grid.find(&'e'), Some(Point::new(1, 1)));
+        assert_eq!(grid.find(&'x'), None);
+        assert!(Grid::parse(Input::new(1, "ab\nc"), "abc").is_err());
+        assert!(Grid::parse(Input::new(1, "ab\nxy"), "abc").is_err());
+    }
+
+    #[test]
+    fn access_is_bounds_checked_or_wrapping() {
+        let grid = grid();
+        assert_eq!(grid.get(Point::new(0, 0)), Some(&'a'));
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+        assert_eq!(grid.get_wrapping(Point::new(-1, 0)), &'d');
+        assert_eq!(grid.get_wrapping(Point::new(4, -4)), &'c');
+        assert_eq!(grid.wrap(Point::new(-3, 7)), Point::new(1, 1));
+    }
+
+    #[test]
+    fn neighbors_stay_on_grid() {
+        let grid = grid();
+        let corner: Vec<Point> = grid.neighbors4(Point::new(0, 0)).collect();
+        assert_eq!(corner, vec![Point::new(0, 1), Point::new(1, 0)]);
+        assert_eq!(grid.neighbors8(Point::new(0, 1)).count(), 5);
+        assert_eq!(Point::new(5, 5).neighbors8().count(), 8);
+        assert_eq!(Point::new(1, -2).manhattan_distance(Point::new(-2, 2)), 7);
+    }
+
+    #[test]
+    fn directions_rotate() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::East.reverse(), Direction::West);
+        assert!(Direction::South.is_vertical());
+        assert_eq!(Point::new(1, 1).step(Direction::West), Point::new(1, 0));
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(
+                direction.offset() + direction.reverse().offset(),
+                Point::default()
+            );
+        }
+    }
+
+    #[test]
+    fn transpose_and_rotate() {
+        let grid = grid();
+        assert_eq!(text(&grid.transpose()), vec!["ad", "be", "cf"]);
+        assert_eq!(text(&grid.rotate_clockwise()), vec!["da", "eb", "fc"]);
+        assert_eq!(
+            text(&grid.rotate_counterclockwise()),
+            vec!["cf", "be", "ad"]
+        );
+        let mut turned = grid.clone();
+        for _ in 0..4 {
+            turned = turned.rotate_clockwise();
+        }
+        assert_eq!(turned, grid);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::clock::Clock;
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The settings of the HTTP client used to talk to the Advent of Code website.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// The User-Agent header sent with every request, identifying this program to the website.
+    pub user_agent: String,
+    /// The shortest time allowed between the start of two requests.
+    pub min_interval: Duration,
+    /// Where the time of the last request is stored, so that the spacing between requests is kept
+    /// across runs. Only requests made by this client are spaced if unset.
+    pub throttle_path: Option<PathBuf>,
+    /// How many times a request is retried after a server error or a failure to connect.
+    pub max_retries: u32,
+    /// The wait before the first retry, which doubles with every later retry.
+    pub backoff: Duration,
+    /// How long to wait for a connection to be established.
+    pub connect_timeout: Duration,
+    /// How long to wait for a whole request to complete.
+    pub timeout: Duration,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: format!(
+                "{}/{} (+https://github.com/akaritakai/AdventOfCode2023 by akaritakai@gmail.com)",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            min_interval: Duration::from_secs(5),
+            throttle_path: None,
+            max_retries: 3,
+            backoff: Duration::from_secs(1),
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// An HTTP client that spaces out its requests and retries those that fail on the server's side.
+pub struct HttpClient {
+    client: Client,
+    config: ClientConfig,
+    clock: Arc<dyn Clock>,
+    /// The time of the last request made by this client.
+    last_request: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl HttpClient {
+    /// Creates a client with the given settings, using the clock to space out requests.
+    pub fn new(config: ClientConfig, clock: Arc<dyn Clock>) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .user_agent(&config.user_agent)
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.timeout)
+            // The website redirects to its login page when the session has expired, which must be
+            // reported rather than followed.
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        Ok(Self {
+            client,
+            config,
+            clock,
+            last_request: Mutex::new(None),
+        })
+    }
+
+    /// Returns a copy of this client that uses the given clock.
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// Sends the request built by the function, waiting first if the previous request was too
+    /// recent. Server errors, timeouts and connection failures are retried with an exponential
+    /// backoff, and the last response is returned once the retries run out. Client errors are
+    /// never retried.
+    pub fn send(
+        &self,
+        request: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Response, Box<dyn Error>> {
+        let mut attempt = 0;
+        loop {
+            self.throttle();
+            let result = request(&self.client).send();
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            if !retryable || attempt >= self.config.max_retries {
+                return Ok(result?);
+            }
+            let backoff = self.config.backoff * 2u32.pow(attempt);
+            self.clock.sleep(chrono::Duration::from_std(backoff)?);
+            attempt += 1;
+        }
+    }
+
+    /// Waits until the minimum interval has passed since the last request (by any run, if the time
+    /// of the last request is stored), then records the current time as that of the last request.
+    fn throttle(&self) {
+        let mut last_request = self.last_request.lock().unwrap();
+        let stored = self.config.throttle_path.as_ref().and_then(|path| {
+            let text = fs::read_to_string(path).ok()?;
+            DateTime::parse_from_rfc3339(text.trim()).ok()
+        });
+        let previous = (*last_request).max(stored.map(|time| time.with_timezone(&Utc)));
+        if let Some(previous) = previous {
+            let interval = chrono::Duration::from_std(self.config.min_interval).unwrap();
+            let wait = previous + interval - self.clock.now();
+            if wait > chrono::Duration::zero() {
+                self.clock.sleep(wait);
+            }
+        }
+        let now = self.clock.now();
+        *last_request = Some(now);
+        if let Some(path) = &self.config.throttle_path {
+            // Failing to store the time only affects the spacing of a later run's requests.
+            let _ = fs::write(path, now.to_rfc3339());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::TimeZone;
+    use httpmock::prelude::*;
+    use tempfile::TempDir;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn config() -> ClientConfig {
+        ClientConfig {
+            min_interval: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    fn get(client: &HttpClient, server: &MockServer) -> Result<Response, Box<dyn Error>> {
+        client.send(|client| client.get(server.url("/2023/day/1/input")))
+    }
+
+    #[test]
+    fn sends_user_agent() {
+        let server = MockServer::start();
+        let config = config();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/2023/day/1/input")
+                .header("User-Agent", &config.user_agent);
+            then.status(200).body("1abc2");
+        });
+        let client = HttpClient::new(config, Arc::new(FixedClock::new(start()))).unwrap();
+        assert_eq!(get(&client, &server).unwrap().text().unwrap(), "1abc2");
+        assert!(client.config.user_agent.contains("github.com"));
+        mock.assert();
+    }
+
+    #[test]
+    fn spaces_out_requests_across_clients() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/2023/day/1/input");
+            then.status(200);
+        });
+        let dir = TempDir::new().unwrap();
+        let config = ClientConfig {
+            min_interval: Duration::from_secs(5),
+            throttle_path: Some(dir.path().join(".last-request")),
+            ..config()
+        };
+        let clock = Arc::new(FixedClock::new(start()));
+        let client = HttpClient::new(config.clone(), clock.clone()).unwrap();
+        get(&client, &server).unwrap();
+        assert_eq!(clock.now(), start());
+        get(&client, &server).unwrap();
+        assert_eq!(clock.now(), start() + chrono::Duration::seconds(5));
+
+        // A new client (as in a later run) waits for the stored time of the last request.
+        clock.sleep(chrono::Duration::seconds(2));
+        let client = HttpClient::new(config, clock.clone()).unwrap();
+        get(&client, &server).unwrap();
+        assert_eq!(clock.now(), start() + chrono::Duration::seconds(10));
+        mock.assert_hits(3);
+    }
+
+    #[test]
+    fn retries_server_errors_with_backoff() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/2023/day/1/input");
+            then.status(503);
+        });
+        let clock = Arc::new(FixedClock::new(start()));
+        let client = HttpClient::new(config(), clock.clone()).unwrap();
+        let response = get(&client, &server).unwrap();
+        assert_eq!(response.status(), 503);
+        mock.assert_hits(4);
+        assert_eq!(clock.now(), start() + chrono::Duration::seconds(1 + 2 + 4));
+    }
+
+    #[test]
+    fn never_retries_client_errors() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/2023/day/1/input");
+            then.status(404);
+        });
+        let clock = Arc::new(FixedClock::new(start()));
+        let client = HttpClient::new(config(), clock.clone()).unwrap();
+        assert_eq!(get(&client, &server).unwrap().status(), 404);
+        mock.assert_hits(1);
+        assert_eq!(clock.now(), start());
+    }
+
+    #[test]
+    fn times_out_slow_responses() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/2023/day/1/input");
+            then.status(200).delay(Duration::from_secs(2));
+        });
+        let config = ClientConfig {
+            timeout: Duration::from_millis(100),
+            max_retries: 1,
+            ..config()
+        };
+        let clock = Arc::new(FixedClock::new(start()));
+        let client = HttpClient::new(config, clock.clone()).unwrap();
+        assert!(get(&client, &server).is_err());
+        mock.assert_hits(2);
+        assert_eq!(clock.now(), start() + chrono::Duration::seconds(1));
+    }
+
+    #[test]
+    fn does_not_follow_redirects() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/2023/day/1/input");
+            then.status(302).header("Location", "/auth/login");
+        });
+        let client = HttpClient::new(config(), Arc::new(FixedClock::new(start()))).unwrap();
+        assert_eq!(get(&client, &server).unwrap().status(), 302);
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a cached input came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputSource {
+    /// Fetched from the Advent of Code website.
+    Remote,
+    /// Placed in the cache by hand.
+    Manual,
+}
+
+/// The record kept next to a cached input, used to detect when the input has been changed or
+/// corrupted since it was cached.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMetadata {
+    /// When the input was fetched (or first found in the cache, if placed by hand).
+    pub fetched: DateTime<Utc>,
+    /// The length of the normalised input in bytes.
+    pub length: usize,
+    /// The lowercase hex-encoded SHA-256 hash of the normalised input.
+    pub sha256: String,
+    pub source: InputSource,
+}
+
+impl InputMetadata {
+    /// Creates the record of a normalised input.
+    pub fn describe(input: &str, source: InputSource, fetched: DateTime<Utc>) -> Self {
+        Self {
+            fetched,
+            length: input.len(),
+            sha256: sha256_hex(input),
+            source,
+        }
+    }
+
+    /// Returns the path of the record kept next to the given input file.
+    pub fn path(input_path: &Path) -> PathBuf {
+        let mut name = input_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".meta.toml");
+        input_path.with_file_name(name)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Checks that the normalised input is the one this record was made for.
+    pub fn verify(&self, input: &str) -> Result<(), String> {
+        if input.len() != self.length {
+            Err(format!(
+                "expected {} bytes, found {}",
+                self.length,
+                input.len()
+            ))
+        } else if !sha256_hex(input).eq_ignore_ascii_case(&self.sha256) {
+            Err("SHA-256 hash does not match".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns the input with Windows line endings replaced by Unix ones.
+pub fn normalize(input: &str) -> String {
+    input.replace("\r\n", "\n")
+}
+
+/// Returns whether the text looks like an HTML page or one of the website's error messages
+/// rather than a puzzle input.
+pub fn looks_like_error_page(text: &str) -> bool {
+    let start = text.trim_start().to_ascii_lowercase();
+    start.starts_with("<!doctype html")
+        || start.starts_with("<html")
+        || text.contains("Please log in to get your puzzle input")
+        || text.contains("Please don't repeatedly request this endpoint before it unlocks")
+        || text.contains("500 Internal Server Error")
+}
+
+/// Returns the lowercase hex-encoded SHA-256 hash of the input.
+pub fn sha256_hex(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        assert_eq!(
+            sha256_hex("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn metadata_round_trips_and_verifies_input() {
+        let dir = TempDir::new().unwrap();
+        let fetched = Utc.with_ymd_and_hms(2023, 12, 1, 5, 0, 3).unwrap();
+        let metadata = InputMetadata::describe("1abc2\n", InputSource::Remote, fetched);
+        let path = InputMetadata::path(&dir.path().join("01"));
+        assert_eq!(path, dir.path().join("01.meta.toml"));
+        metadata.save(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("source = \"remote\""));
+        assert!(text.contains("length = 6"));
+
+        let loaded = InputMetadata::load(&path).unwrap();
+        assert_eq!(loaded, metadata);
+        assert!(loaded.verify("1abc2\n").is_ok());
+        assert!(loaded.verify("1abc3\n").is_err());
+        assert!(loaded.verify("1abc2").is_err());
+    }
+
+    #[test]
+    fn normalize_replaces_windows_line_endings() {
+        assert_eq!(normalize("a\r\nb\r\n"), "a\nb\n");
+        assert_eq!(normalize("a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn error_pages_are_detected() {
+        assert!(looks_like_error_page(
+            "<!DOCTYPE html>\n<html lang=\"en-us\">"
+        ));
+        assert!(looks_like_error_page("\n<html><body>Oops</body></html>"));
+        assert!(looks_like_error_page(
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+        ));
+        assert!(!looks_like_error_page("467..114..\n...*......\n"));
+        assert!(!looks_like_error_page("<>^v\n"));
+    }
+}
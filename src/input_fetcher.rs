@@ -1,87 +1,885 @@
+use crate::clock::{format_duration, unlock_time, Clock, SystemClock};
+use crate::http_client::{ClientConfig, HttpClient};
+use crate::input_cache::{looks_like_error_page, normalize, InputMetadata, InputSource};
+use crate::leaderboard;
+use crate::leaderboard::Leaderboard;
+use crate::registry;
+use crate::secure_store::SecureStore;
+use chrono::{DateTime, Utc};
+use lazy_regex::regex_captures;
 use reqwest::StatusCode;
+use scraper::{Html, Selector};
+use std::collections::BTreeMap;
+use std::env;
 use std::error::Error;
+use std::fmt::Display;
 use std::fs;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The environment variable that takes precedence over every other source of the session token.
+pub const SESSION_ENV_VAR: &str = "AOC_SESSION";
+
+/// The year of the inputs stored directly in the input directory, before it held one directory
+/// per year.
+const FLAT_LAYOUT_YEAR: u16 = 2023;
+
+/// Something fetched from the website, with the error that prevented fetching it if it is a stale
+/// cached copy instead.
+pub type MaybeStale<T> = (T, Option<Box<dyn Error>>);
 
 pub struct InputFetcher {
     /// The base URL for Advent of Code (by default 'https://adventofcode.com').
     base_url: String,
-    /// The location where the puzzle inputs are locally stored (by default 'puzzle').
+    /// The location where the puzzle inputs are locally stored (by default 'puzzle'), with one
+    /// directory per year.
     input_path: PathBuf,
-    /// The location where the session token is locally stored (by default 'cookie.txt').
-    session_token_path: PathBuf,
+    /// The year of the puzzles fetched (by default the latest year with solutions).
+    year: u16,
+    /// The places the session token is read from, in order of precedence.
+    session_token_sources: Vec<TokenSource>,
+    /// The clock used to decide whether a day's puzzle has unlocked and to space out requests.
+    clock: Arc<dyn Clock>,
+    /// The client that every request to the Advent of Code website is sent through.
+    http: HttpClient,
+    /// Whether a request for a locked day waits until it unlocks instead of failing.
+    wait_for_unlock: bool,
+    /// The store that cached inputs are encrypted with, if they are kept encrypted.
+    store: Option<SecureStore>,
 }
 
 impl InputFetcher {
     /// Creates an InputFetcher using the default values.
     pub fn create() -> Self {
-        Self::create_custom(
+        Self::create_with_session_file(None)
+    }
+
+    /// Creates an InputFetcher using the default values, reading the session token from the first
+    /// of these that is set: the `AOC_SESSION` environment variable, the given file,
+    /// `$XDG_CONFIG_HOME/aoc/session` (or `~/.config/aoc/session`) and `cookie.txt`.
+    pub fn create_with_session_file(session_file: Option<&Path>) -> Self {
+        Self::create_with_sources(
             "https://adventofcode.com",
             Path::new("puzzle"),
-            Path::new("cookie.txt"),
+            default_token_sources(session_file, config_dir()),
         )
     }
 
     /// Creates an InputFetcher using the specified values. Used only for testing.
     pub fn create_custom(base_url: &str, input_path: &Path, session_token_path: &Path) -> Self {
+        Self::create_with_sources(
+            base_url,
+            input_path,
+            vec![TokenSource::File(session_token_path.to_path_buf())],
+        )
+    }
+
+    fn create_with_sources(
+        base_url: &str,
+        input_path: &Path,
+        session_token_sources: Vec<TokenSource>,
+    ) -> Self {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let http = HttpClient::new(default_client_config(input_path), clock.clone())
+            .expect("Failed to create the HTTP client");
         Self {
             base_url: base_url.to_string(),
             input_path: input_path.to_path_buf(),
-            session_token_path: session_token_path.to_path_buf(),
+            year: registry::latest_year().year,
+            session_token_sources,
+            clock,
+            http,
+            wait_for_unlock: false,
+            store: None,
         }
     }
 
+    /// Sets the year of the puzzles fetched.
+    pub fn for_year(mut self, year: u16) -> Self {
+        self.year = year;
+        self
+    }
+
+    /// Returns the year of the puzzles fetched.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Moves the files of an input directory laid out before it held one directory per year
+    /// (such as `puzzle/01` and `puzzle/guesses`) into the directory of the year they belong to.
+    /// Files that already exist there are left in place. Returns the number of files moved.
+    pub fn migrate_flat_layout(&self) -> io::Result<usize> {
+        if !self.input_path.exists() {
+            return Ok(0);
+        }
+        let year_dir = self.input_path.join(FLAT_LAYOUT_YEAR.to_string());
+        let mut moved = 0;
+        for entry in fs::read_dir(&self.input_path)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // Day files are named after their day, optionally followed by an extension.
+            let is_day_file = name
+                .get(..2)
+                .and_then(|day| day.parse::<u8>().ok())
+                .is_some_and(|day| (1..=25).contains(&day))
+                && name
+                    .get(2..)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+            let target = year_dir.join(name);
+            if (is_day_file || name == "guesses") && !target.exists() {
+                fs::create_dir_all(&year_dir)?;
+                fs::rename(&path, &target)?;
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Replaces the clock used to decide whether a day's puzzle has unlocked and to space out
+    /// requests.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self.http = self.http.with_clock(self.clock.clone());
+        self
+    }
+
+    /// Replaces the settings of the HTTP client. By default requests are spaced out by at least
+    /// five seconds, with the time of the last request stored next to the inputs.
+    pub fn with_client_config(mut self, config: ClientConfig) -> Result<Self, Box<dyn Error>> {
+        self.http = HttpClient::new(config, self.clock.clone())?;
+        Ok(self)
+    }
+
+    /// Sets whether a request for a day that has not unlocked yet waits until it unlocks, rather
+    /// than failing with the time left to wait.
+    pub fn wait_for_unlock(mut self, wait: bool) -> Self {
+        self.wait_for_unlock = wait;
+        self
+    }
+
+    /// Keeps cached inputs encrypted with the store. Encrypted inputs and session token files are
+    /// decrypted when read, and inputs found unencrypted in the cache are encrypted in place.
+    pub fn with_store(mut self, store: SecureStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Returns whether a request for a day that has not unlocked yet waits until it unlocks.
+    pub fn waits_for_unlock(&self) -> bool {
+        self.wait_for_unlock
+    }
+
+    /// Returns the store that cached inputs are encrypted with, if any.
+    pub fn store(&self) -> Option<&SecureStore> {
+        self.store.as_ref()
+    }
+
+    /// Reads a file as text, decrypting it with the store if it is encrypted.
+    pub fn read_file(&self, path: &Path) -> Result<String, Box<dyn Error>> {
+        Ok(SecureStore::read(self.store(), path)?.0)
+    }
+
     /// Returns the input for the given day. Will try to return it from the local file system first,
     /// and if that fails, will try to fetch it from the Advent of Code website.
     pub fn get_input(&self, day: u8) -> Result<String, Box<dyn Error>> {
-        let input_file_path = self.input_path.join(format!("{:02}", day));
-        if input_file_path.exists() {
-            Ok(fs::read_to_string(input_file_path)?)
+        match self.get_cached_input(day)? {
+            Some(input) => Ok(input),
+            None => self.fetch_and_cache_input(day),
+        }
+    }
+
+    /// Returns the cached input for the given day, or `None` if it has not been cached. An input
+    /// placed in the cache by hand is recorded in a metadata file next to it, and an input whose
+    /// metadata no longer matches it (or that is an error page) is rejected. Windows line endings
+    /// are normalised away.
+    pub fn get_cached_input(&self, day: u8) -> Result<Option<String>, Box<dyn Error>> {
+        let input_file_path = self.input_file_path(day);
+        if !input_file_path.exists() {
+            return Ok(None);
+        }
+        let (raw, encrypted) = SecureStore::read(self.store(), &input_file_path)?;
+        let input = self.check_cached_input(day, &raw)?;
+        let metadata_path = InputMetadata::path(&input_file_path);
+        if !metadata_path.exists() {
+            InputMetadata::describe(&input, InputSource::Manual, self.clock.now())
+                .save(&metadata_path)?;
+        }
+        if input != raw || (self.store.is_some() && !encrypted) {
+            SecureStore::write(self.store(), &input_file_path, &input)?;
+        }
+        Ok(Some(input))
+    }
+
+    /// Checks the cached input for the given day like [`InputFetcher::get_cached_input`], without
+    /// changing the cache: an input placed by hand is not recorded, nor are its line endings
+    /// normalised or the file encrypted. An input that has not been cached passes.
+    pub fn verify_cached_input(&self, day: u8) -> Result<(), String> {
+        let input_file_path = self.input_file_path(day);
+        if !input_file_path.exists() {
+            return Ok(());
+        }
+        let (raw, _) =
+            SecureStore::read(self.store(), &input_file_path).map_err(|e| e.to_string())?;
+        self.check_cached_input(day, &raw)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the normalised cached input for the given day, failing if it is an error page or
+    /// its metadata (if it has any) no longer matches it.
+    fn check_cached_input(&self, day: u8, raw: &str) -> Result<String, Box<dyn Error>> {
+        let input_file_path = self.input_file_path(day);
+        let corrupt = |reason: &str| -> Box<dyn Error> {
+            format!(
+                "Cached input {} is corrupt ({}); replace it with `aoc2023 --year {} refetch --day {}`",
+                input_file_path.display(),
+                reason,
+                self.year,
+                day
+            )
+            .into()
+        };
+        if looks_like_error_page(raw) {
+            return Err(corrupt("it is an error page"));
+        }
+        let input = normalize(raw);
+        let metadata_path = InputMetadata::path(&input_file_path);
+        if metadata_path.exists() {
+            InputMetadata::load(&metadata_path)?
+                .verify(&input)
+                .map_err(|e| corrupt(&e))?;
+        }
+        Ok(input)
+    }
+
+    /// Fetches the input for the given day again, replacing the cached copy and its metadata.
+    pub fn refetch_input(&self, day: u8) -> Result<String, Box<dyn Error>> {
+        // The cached copy is only replaced once the new one has been fetched.
+        self.fetch_and_cache_input(day)
+    }
+
+    fn fetch_and_cache_input(&self, day: u8) -> Result<String, Box<dyn Error>> {
+        let session_token = self.get_session_token()?;
+        let fetched = self.fetch_input(day, &session_token)?;
+        if looks_like_error_page(&fetched) {
+            return Err(format!("Fetched input for day {} is an error page", day).into());
+        }
+        let input = normalize(&fetched);
+        let input_file_path = self.input_file_path(day);
+        fs::create_dir_all(self.year_dir())?;
+        SecureStore::write(self.store(), &input_file_path, &input)?;
+        InputMetadata::describe(&input, InputSource::Remote, self.clock.now())
+            .save(&InputMetadata::path(&input_file_path))?;
+        Ok(input)
+    }
+
+    /// Returns the directory where the inputs and other files of the year are stored.
+    fn year_dir(&self) -> PathBuf {
+        self.input_path.join(self.year.to_string())
+    }
+
+    fn input_file_path(&self, day: u8) -> PathBuf {
+        self.year_dir().join(format!("{:02}", day))
+    }
+
+    /// Returns the description of the given day's puzzle as Markdown, including part 2 once it has
+    /// been unlocked. The Markdown conversion is saved next to the cached page.
+    pub fn get_description(&self, day: u8) -> Result<String, Box<dyn Error>> {
+        let markdown = description_to_markdown(&self.get_page(day)?)?;
+        fs::write(self.year_dir().join(format!("{:02}.md", day)), &markdown)?;
+        Ok(markdown)
+    }
+
+    /// Returns the HTML page of the given day's puzzle. The page is cached next to the inputs, and
+    /// is only fetched again while the cached copy is missing part 2: once part 1 is submitted
+    /// correctly, or every so often in case it was solved elsewhere.
+    pub fn get_page(&self, day: u8) -> Result<String, Box<dyn Error>> {
+        let html_path = self.page_path(day);
+        let fetched_path = html_path.with_extension("fetched");
+        let cached = if html_path.exists() {
+            Some(fs::read_to_string(&html_path)?)
         } else {
-            let session_token = self.get_session_token()?;
-            let input = self.fetch_input(day, &session_token)?;
-            fs::write(input_file_path, &input)?;
-            Ok(input)
+            None
+        };
+        let fetched = fetched_at(&fetched_path);
+        Ok(match cached {
+            Some(html) if description_articles(&html).len() >= 2 => html,
+            Some(html)
+                if fetched.is_some_and(|fetched| {
+                    self.clock.now() - fetched < page_refresh_interval()
+                }) =>
+            {
+                html
+            }
+            cached => {
+                let fetched = self
+                    .get_session_token()
+                    .and_then(|session_token| self.fetch_description(day, &session_token));
+                match (fetched, cached) {
+                    (Ok(html), _) => {
+                        fs::create_dir_all(self.year_dir())?;
+                        fs::write(&html_path, &html)?;
+                        fs::write(&fetched_path, self.clock.now().to_rfc3339())?;
+                        html
+                    }
+                    // Part 1 alone is better than nothing while offline.
+                    (Err(_), Some(html)) => html,
+                    (Err(e), None) => return Err(e),
+                }
+            }
+        })
+    }
+
+    fn page_path(&self, day: u8) -> PathBuf {
+        self.year_dir().join(format!("{:02}.html", day))
+    }
+
+    /// Submits the answer to the given part of a day and returns the outcome. Wrong answers are
+    /// recorded locally, and submitting one again returns its recorded outcome without contacting
+    /// the Advent of Code website. An answer containing a tab or line break is rejected unsent.
+    pub fn submit_answer(
+        &self,
+        day: u8,
+        part: u8,
+        answer: &str,
+    ) -> Result<SubmitOutcome, Box<dyn Error>> {
+        // Guesses are recorded one per line with a tab before the outcome, and no answer spans
+        // lines or contains a tab anyway.
+        if answer.contains(['\t', '\n', '\r']) {
+            return Err(format!("Answer {:?} contains a tab or line break", answer).into());
+        }
+        if let Some(outcome) = self.previous_guess(day, part, answer)? {
+            return Ok(outcome);
+        }
+        let session_token = self.get_session_token()?;
+        let outcome = self.post_answer(day, part, answer, &session_token)?;
+        if outcome.is_wrong() {
+            self.record_guess(day, part, answer, outcome)?;
+        }
+        if part == 1 && outcome == SubmitOutcome::Correct {
+            // Part 2 has just unlocked, so the cached page is out of date.
+            let fetched_path = self.page_path(day).with_extension("fetched");
+            if fetched_path.exists() {
+                fs::remove_file(fetched_path)?;
+            }
         }
+        Ok(outcome)
     }
 
+    /// Returns the private leaderboard with the given ID. The leaderboard is cached next to the
+    /// inputs and only fetched again once the cached copy is older than the website's polling
+    /// guidance allows; the cached copy is also used if it cannot be fetched, in which case the
+    /// error that prevented fetching it is returned alongside it.
+    pub fn get_leaderboard(&self, id: u64) -> Result<MaybeStale<Leaderboard>, Box<dyn Error>> {
+        let path = self.leaderboard_path(id);
+        let fetched_path = path.with_extension("fetched");
+        let cached = if path.exists() {
+            Some(fs::read_to_string(&path)?)
+        } else {
+            None
+        };
+        if let (Some(json), Some(fetched)) = (&cached, fetched_at(&fetched_path)) {
+            if self.clock.now() - fetched < leaderboard::refresh_interval() {
+                return Ok((Leaderboard::parse(json)?, None));
+            }
+        }
+        let fetched = self
+            .get_session_token()
+            .and_then(|session_token| self.fetch_leaderboard(id, &session_token));
+        match (fetched, cached) {
+            (Ok(json), _) => {
+                let leaderboard = Leaderboard::parse(&json)?;
+                fs::create_dir_all(self.year_dir().join("leaderboards"))?;
+                fs::write(&path, &json)?;
+                fs::write(&fetched_path, self.clock.now().to_rfc3339())?;
+                Ok((leaderboard, None))
+            }
+            (Err(e), Some(json)) => Ok((Leaderboard::parse(&json)?, Some(e))),
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    /// Returns how many stars the user has for each day of the year, read from the year's calendar
+    /// page. Days that have not unlocked are omitted.
+    pub fn get_stars(&self) -> Result<BTreeMap<u8, u8>, Box<dyn Error>> {
+        let session_token = self.get_session_token()?;
+        let url = format!("{}{}", self.base_url, calendar_url_path(self.year));
+        let response = self.http.send(|client| {
+            client
+                .get(&url)
+                .header("Cookie", format!("session={}", session_token))
+        })?;
+        if response.status() != StatusCode::OK {
+            return Err(format!("Failed to fetch calendar: {}", response.status()).into());
+        }
+        let html = response.text()?;
+        // The calendar is shown without any stars to visitors who are not logged in.
+        if user_name(&html).is_none() {
+            return Err("Calendar was shown logged out; the session token may have expired".into());
+        }
+        Ok(calendar_stars(&html))
+    }
+
+    /// Checks that the session token is accepted by the Advent of Code website, returning the name
+    /// of the user it belongs to if the website shows one.
+    pub fn check_session_token(&self) -> Result<Option<String>, Box<dyn Error>> {
+        let session_token = self.get_session_token()?;
+        let url = format!("{}{}", self.base_url, SETTINGS_URL_PATH);
+        // Without a valid session the settings page redirects to the login page.
+        let response = self.http.send(|client| {
+            client
+                .get(&url)
+                .header("Cookie", format!("session={}", session_token))
+        })?;
+        if response.status() == StatusCode::OK {
+            Ok(user_name(&response.text()?))
+        } else {
+            Err(format!(
+                "Session token was rejected ({}); it may have expired",
+                response.status()
+            )
+            .into())
+        }
+    }
+
+    /// Returns the session token from the first source that has one, failing if it is malformed.
     fn get_session_token(&self) -> Result<String, Box<dyn Error>> {
-        let session_token_file = File::open(&self.session_token_path)?;
-        let mut session_token_reader = BufReader::new(session_token_file);
-        let mut session_token = String::new();
-        session_token_reader.read_to_string(&mut session_token)?;
-        Ok(session_token)
+        for source in &self.session_token_sources {
+            if let Some(session_token) = source.read(self.store())? {
+                return validate_session_token(&session_token)
+                    .map(|()| session_token)
+                    .map_err(|e| format!("Invalid session token in {}: {}", source, e).into());
+            }
+        }
+        let sources: Vec<String> = self
+            .session_token_sources
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        Err(format!("No session token found (tried {})", sources.join(", ")).into())
+    }
+
+    /// Returns how long is left until the given day's puzzle unlocks, or `None` if it has.
+    pub fn time_until_unlock(&self, day: u8) -> Result<Option<chrono::Duration>, Box<dyn Error>> {
+        let unlocks = unlock_time(self.year, day)
+            .ok_or_else(|| format!("There is no day {} (days run from 1 to 25)", day))?;
+        let remaining = unlocks - self.clock.now();
+        Ok((remaining > chrono::Duration::zero()).then_some(remaining))
+    }
+
+    /// Fails (or waits, if configured to) while the given day's puzzle has not unlocked, since the
+    /// website asks not to be sent requests for it before then.
+    fn ensure_unlocked(&self, day: u8) -> Result<(), Box<dyn Error>> {
+        let Some(remaining) = self.time_until_unlock(day)? else {
+            return Ok(());
+        };
+        if self.wait_for_unlock {
+            self.clock.sleep(remaining);
+            Ok(())
+        } else {
+            Err(format!("Day {} unlocks in {}", day, format_duration(remaining)).into())
+        }
     }
 
     fn fetch_input(&self, day: u8, session_token: &str) -> Result<String, Box<dyn Error>> {
-        let url = format!("{}{}", self.base_url, url_path(day));
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .get(url)
-            .header("Cookie", format!("session={}", session_token))
-            .send()?;
+        self.ensure_unlocked(day)?;
+        let url = format!("{}{}", self.base_url, url_path(self.year, day));
+        let response = self.http.send(|client| {
+            client
+                .get(&url)
+                .header("Cookie", format!("session={}", session_token))
+        })?;
         if response.status() == StatusCode::OK {
             Ok(response.text()?)
         } else {
             Err(format!("Failed to fetch input: {}", response.status()).into())
         }
     }
+
+    fn fetch_description(&self, day: u8, session_token: &str) -> Result<String, Box<dyn Error>> {
+        self.ensure_unlocked(day)?;
+        let url = format!("{}{}", self.base_url, description_url_path(self.year, day));
+        let response = self.http.send(|client| {
+            client
+                .get(&url)
+                .header("Cookie", format!("session={}", session_token))
+        })?;
+        if response.status() == StatusCode::OK {
+            Ok(response.text()?)
+        } else {
+            Err(format!("Failed to fetch description: {}", response.status()).into())
+        }
+    }
+
+    fn fetch_leaderboard(&self, id: u64, session_token: &str) -> Result<String, Box<dyn Error>> {
+        let url = format!("{}{}", self.base_url, leaderboard_url_path(self.year, id));
+        // Without a valid session, or for a leaderboard the user has not joined, the website
+        // redirects elsewhere.
+        let response = self.http.send(|client| {
+            client
+                .get(&url)
+                .header("Cookie", format!("session={}", session_token))
+        })?;
+        if response.status() == StatusCode::OK {
+            Ok(response.text()?)
+        } else {
+            Err(format!("Failed to fetch leaderboard: {}", response.status()).into())
+        }
+    }
+
+    /// Returns the location where the private leaderboard with the given ID is cached. The time it
+    /// was fetched is kept next to it, with the extension `fetched`.
+    fn leaderboard_path(&self, id: u64) -> PathBuf {
+        self.year_dir()
+            .join("leaderboards")
+            .join(format!("{}.json", id))
+    }
+
+    fn post_answer(
+        &self,
+        day: u8,
+        part: u8,
+        answer: &str,
+        session_token: &str,
+    ) -> Result<SubmitOutcome, Box<dyn Error>> {
+        self.ensure_unlocked(day)?;
+        let url = format!("{}{}", self.base_url, answer_url_path(self.year, day));
+        let response = self.http.send(|client| {
+            client
+                .post(&url)
+                .header("Cookie", format!("session={}", session_token))
+                .form(&[("level", part.to_string()), ("answer", answer.to_string())])
+        })?;
+        if response.status() == StatusCode::OK {
+            parse_submit_response(&response.text()?)
+        } else {
+            Err(format!("Failed to submit answer: {}", response.status()).into())
+        }
+    }
+
+    /// Returns the location where wrong answers to the given part of a day are recorded, one per
+    /// line as the answer and its outcome separated by a tab.
+    fn guesses_path(&self, day: u8, part: u8) -> PathBuf {
+        self.year_dir()
+            .join("guesses")
+            .join(format!("{:02}-{}", day, part))
+    }
+
+    fn previous_guess(
+        &self,
+        day: u8,
+        part: u8,
+        answer: &str,
+    ) -> Result<Option<SubmitOutcome>, Box<dyn Error>> {
+        let path = self.guesses_path(day, part);
+        if !path.exists() {
+            return Ok(None);
+        }
+        for line in fs::read_to_string(path)?.lines() {
+            if let Some((guess, outcome)) = line.split_once('\t') {
+                if guess == answer {
+                    return Ok(Some(SubmitOutcome::parse_wrong(outcome).ok_or_else(
+                        || format!("Invalid recorded outcome '{}'", outcome),
+                    )?));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn record_guess(
+        &self,
+        day: u8,
+        part: u8,
+        answer: &str,
+        outcome: SubmitOutcome,
+    ) -> Result<(), Box<dyn Error>> {
+        let path = self.guesses_path(day, part);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}\t{}", answer, outcome)?;
+        Ok(())
+    }
+}
+
+/// A place the session token may be read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    /// An environment variable.
+    Env(String),
+    /// A file holding only the token.
+    File(PathBuf),
+}
+
+impl TokenSource {
+    /// Returns the token with surrounding whitespace removed, or `None` if the source is unset
+    /// or empty. A token file may be encrypted with the store.
+    fn read(&self, store: Option<&SecureStore>) -> Result<Option<String>, Box<dyn Error>> {
+        let token = match self {
+            TokenSource::Env(name) => env::var(name).ok(),
+            TokenSource::File(path) if path.exists() => Some(SecureStore::read(store, path)?.0),
+            TokenSource::File(_) => None,
+        };
+        Ok(token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty()))
+    }
+}
+
+impl Display for TokenSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TokenSource::Env(name) => write!(f, "${}", name),
+            TokenSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Returns the default settings of the HTTP client, storing the time of the last request next to
+/// the inputs.
+fn default_client_config(input_path: &Path) -> ClientConfig {
+    ClientConfig {
+        throttle_path: Some(input_path.join(".last-request")),
+        ..Default::default()
+    }
+}
+
+/// Returns the sources of the session token in order of precedence.
+fn default_token_sources(
+    session_file: Option<&Path>,
+    config_dir: Option<PathBuf>,
+) -> Vec<TokenSource> {
+    let mut sources = vec![TokenSource::Env(SESSION_ENV_VAR.to_string())];
+    sources.extend(session_file.map(|path| TokenSource::File(path.to_path_buf())));
+    sources.extend(config_dir.map(|dir| TokenSource::File(dir.join("aoc").join("session"))));
+    sources.push(TokenSource::File(PathBuf::from("cookie.txt")));
+    sources
+}
+
+/// Returns the user's configuration directory: `$XDG_CONFIG_HOME`, or `~/.config` if unset.
+fn config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    }
+}
+
+/// Checks that a session token is 128 hexadecimal digits, without echoing it in the error.
+fn validate_session_token(session_token: &str) -> Result<(), String> {
+    if session_token.len() != 128 {
+        Err(format!(
+            "expected 128 hexadecimal digits, found {} characters",
+            session_token.chars().count()
+        ))
+    } else if !session_token.chars().all(|c| c.is_ascii_hexdigit()) {
+        Err("expected only hexadecimal digits".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the name of the logged-in user shown in the header of a page.
+fn user_name(html: &str) -> Option<String> {
+    let selector = Selector::parse("div.user").unwrap();
+    let document = Html::parse_document(html);
+    let user = document.select(&selector).next()?;
+    let name = user.text().next()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Returns the stars shown for each unlocked day on a calendar page. Each unlocked day is a link
+/// with the class `calendar-dayN`, and the class `calendar-complete` or `calendar-verycomplete`
+/// once it has one or two stars.
+fn calendar_stars(html: &str) -> BTreeMap<u8, u8> {
+    let selector = Selector::parse("a[class]").unwrap();
+    let document = Html::parse_document(html);
+    let mut stars = BTreeMap::new();
+    for link in document.select(&selector) {
+        let classes: Vec<&str> = link.value().classes().collect();
+        let Some(day) = classes
+            .iter()
+            .find_map(|class| class.strip_prefix("calendar-day")?.parse::<u8>().ok())
+        else {
+            continue;
+        };
+        let count = if classes.contains(&"calendar-verycomplete") {
+            2
+        } else if classes.contains(&"calendar-complete") {
+            1
+        } else {
+            0
+        };
+        stars.insert(day, count);
+    }
+    stars
+}
+
+/// The result of submitting an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitOutcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// The answer is wrong, without a hint as to whether it is too high or too low.
+    Wrong,
+    /// An answer was submitted too recently, and another may be submitted after the wait.
+    RateLimited {
+        wait: Duration,
+    },
+    /// The part has already been solved.
+    AlreadySolved,
+}
+
+impl SubmitOutcome {
+    /// Returns whether the answer was rejected as incorrect.
+    pub fn is_wrong(&self) -> bool {
+        matches!(
+            self,
+            SubmitOutcome::TooHigh | SubmitOutcome::TooLow | SubmitOutcome::Wrong
+        )
+    }
+
+    fn parse_wrong(text: &str) -> Option<Self> {
+        match text {
+            "too high" => Some(SubmitOutcome::TooHigh),
+            "too low" => Some(SubmitOutcome::TooLow),
+            "wrong" => Some(SubmitOutcome::Wrong),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SubmitOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SubmitOutcome::Correct => write!(f, "correct"),
+            SubmitOutcome::TooHigh => write!(f, "too high"),
+            SubmitOutcome::TooLow => write!(f, "too low"),
+            SubmitOutcome::Wrong => write!(f, "wrong"),
+            SubmitOutcome::RateLimited { wait } => {
+                write!(f, "rate limited ({}s left to wait)", wait.as_secs())
+            }
+            SubmitOutcome::AlreadySolved => write!(f, "already solved"),
+        }
+    }
+}
+
+/// Reads the outcome of a submission from the message in the returned HTML page.
+fn parse_submit_response(html: &str) -> Result<SubmitOutcome, Box<dyn Error>> {
+    if html.contains("That's the right answer") {
+        Ok(SubmitOutcome::Correct)
+    } else if html.contains("That's not the right answer") {
+        if html.contains("your answer is too high") {
+            Ok(SubmitOutcome::TooHigh)
+        } else if html.contains("your answer is too low") {
+            Ok(SubmitOutcome::TooLow)
+        } else {
+            Ok(SubmitOutcome::Wrong)
+        }
+    } else if html.contains("You gave an answer too recently") {
+        let (_, minutes, seconds) =
+            regex_captures!(r"You have (?:(\d+)m )?(\d+)s left to wait", html)
+                .ok_or("Failed to read the wait time from the response")?;
+        let minutes = if minutes.is_empty() {
+            0
+        } else {
+            minutes.parse::<u64>()?
+        };
+        let wait = Duration::from_secs(60 * minutes + seconds.parse::<u64>()?);
+        Ok(SubmitOutcome::RateLimited { wait })
+    } else if html.contains("Did you already complete it?") {
+        Ok(SubmitOutcome::AlreadySolved)
+    } else {
+        Err("Unrecognized response to answer submission".into())
+    }
+}
+
+/// Returns the HTML of each part's description on a puzzle page.
+/// How long a cached puzzle page without part 2 is used before it is fetched again, in case part 1
+/// has since been solved on the website.
+fn page_refresh_interval() -> chrono::Duration {
+    chrono::Duration::hours(1)
 }
 
-fn url_path(day: u8) -> String {
-    format!("/2023/day/{}/input", day)
+/// Returns when something was fetched, as recorded in the given file.
+fn fetched_at(path: &Path) -> Option<DateTime<Utc>> {
+    let text = fs::read_to_string(path).ok()?;
+    let fetched = DateTime::parse_from_rfc3339(text.trim()).ok()?;
+    Some(fetched.with_timezone(&Utc))
 }
 
+fn description_articles(html: &str) -> Vec<String> {
+    let selector = Selector::parse("article.day-desc").unwrap();
+    Html::parse_document(html)
+        .select(&selector)
+        .map(|article| article.html())
+        .collect()
+}
+
+/// Converts the descriptions on a puzzle page to Markdown, one section per part.
+fn description_to_markdown(html: &str) -> Result<String, Box<dyn Error>> {
+    let articles = description_articles(html);
+    if articles.is_empty() {
+        return Err("No puzzle description found in the page".into());
+    }
+    let sections: Vec<String> = articles
+        .iter()
+        .map(|article| html2md::parse_html(article).trim().to_string())
+        .collect();
+    Ok(sections.join("\n\n") + "\n")
+}
+
+fn url_path(year: u16, day: u8) -> String {
+    format!("/{}/day/{}/input", year, day)
+}
+
+fn description_url_path(year: u16, day: u8) -> String {
+    format!("/{}/day/{}", year, day)
+}
+
+fn answer_url_path(year: u16, day: u8) -> String {
+    format!("/{}/day/{}/answer", year, day)
+}
+
+fn calendar_url_path(year: u16) -> String {
+    format!("/{}", year)
+}
+
+fn leaderboard_url_path(year: u16, id: u64) -> String {
+    format!("/{}/leaderboard/private/view/{}.json", year, id)
+}
+
+const SETTINGS_URL_PATH: &str = "/settings";
+
 #[cfg(test)]
 mod tests {
-    use crate::input_fetcher::{url_path, InputFetcher};
+    use crate::clock::{unlock_time, Clock, FixedClock};
+    use crate::input_cache::{sha256_hex, InputMetadata, InputSource};
+    use crate::input_fetcher::{
+        answer_url_path, calendar_stars, calendar_url_path, default_token_sources,
+        description_url_path, leaderboard_url_path, url_path, validate_session_token, InputFetcher,
+        SubmitOutcome, TokenSource, SETTINGS_URL_PATH,
+    };
+    use crate::secure_store::{is_encrypted, SecureStore};
     use httpmock::prelude::*;
     use httpmock::Mock;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
     use tempfile::{NamedTempFile, TempDir};
 
+    /// The number of requests made for a server error: the first and every default retry.
+    const ATTEMPTS: usize = 4;
+
     #[test]
     fn local_fetch_succeeds_without_remote_access() {
         let context = TestContext::create();
@@ -106,12 +904,203 @@ mod tests {
             mock.assert();
 
             // Verify that local files were created.
-            let input_file_path = context.input_dir.path().join(format!("{:02}", day));
+            let input_file_path = context.year_dir().join(format!("{:02}", day));
             let input = std::fs::read_to_string(input_file_path).unwrap();
             assert_eq!(input, context.get_input(day));
         }
     }
 
+    #[test]
+    fn remote_fetch_records_metadata() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        context.delete_puzzle_input_file(3);
+        let mock = context.server_up_mock(3);
+        fetcher.get_input(3).unwrap();
+        mock.assert();
+        let metadata = InputMetadata::load(&context.year_dir().join("03.meta.toml")).unwrap();
+        assert_eq!(metadata.source, InputSource::Remote);
+        assert_eq!(metadata.length, context.get_input(3).len());
+        assert_eq!(metadata.sha256, sha256_hex(context.get_input(3)));
+        assert_eq!(metadata.fetched, TestContext::clock().now());
+    }
+
+    #[test]
+    fn manual_input_is_recorded_and_verified() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let input_path = context.year_dir().join("03");
+        assert_eq!(fetcher.get_input(3).unwrap(), context.get_input(3));
+        let metadata = InputMetadata::load(&context.year_dir().join("03.meta.toml")).unwrap();
+        assert_eq!(metadata.source, InputSource::Manual);
+
+        std::fs::write(&input_path, "edited").unwrap();
+        let error = fetcher.get_input(3).unwrap_err().to_string();
+        assert!(
+            error.contains("aoc2023 --year 2023 refetch --day 3"),
+            "{}",
+            error
+        );
+
+        let mock = context.server_up_mock(3);
+        assert_eq!(fetcher.refetch_input(3).unwrap(), context.get_input(3));
+        mock.assert();
+        assert_eq!(fetcher.get_input(3).unwrap(), context.get_input(3));
+    }
+
+    #[test]
+    fn verifying_cached_input_leaves_cache_unchanged() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let input_path = context.year_dir().join("02");
+        std::fs::write(&input_path, "Game 1: 3 blue\r\n").unwrap();
+        assert_eq!(fetcher.verify_cached_input(2), Ok(()));
+        assert_eq!(
+            std::fs::read_to_string(&input_path).unwrap(),
+            "Game 1: 3 blue\r\n"
+        );
+        assert!(!context.year_dir().join("02.meta.toml").exists());
+
+        assert!(fetcher.get_input(2).is_ok());
+        std::fs::write(&input_path, "edited").unwrap();
+        let error = fetcher.verify_cached_input(2).unwrap_err();
+        assert!(error.contains("refetch --day 2"), "{}", error);
+        context.delete_puzzle_input_file(2);
+        assert_eq!(fetcher.verify_cached_input(2), Ok(()));
+    }
+
+    #[test]
+    fn cached_input_line_endings_are_normalised() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let input_path = context.year_dir().join("02");
+        std::fs::write(&input_path, "Game 1: 3 blue\r\nGame 2: 1 red\r\n").unwrap();
+        let expected = "Game 1: 3 blue\nGame 2: 1 red\n";
+        assert_eq!(fetcher.get_input(2).unwrap(), expected);
+        assert_eq!(std::fs::read_to_string(&input_path).unwrap(), expected);
+
+        // Line endings changed after the input was recorded are not mistaken for corruption.
+        std::fs::write(&input_path, "Game 1: 3 blue\r\nGame 2: 1 red\r\n").unwrap();
+        assert_eq!(fetcher.get_input(2).unwrap(), expected);
+    }
+
+    #[test]
+    fn error_pages_are_never_used_as_input() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let page = "<!DOCTYPE html>\n<html><body>500 Internal Server Error</body></html>";
+        std::fs::write(context.year_dir().join("04"), page).unwrap();
+        assert!(fetcher.get_input(4).is_err());
+
+        context.delete_puzzle_input_file(4);
+        let mock = context.server.mock(|when, then| {
+            when.method(GET).path(url_path(2023, 4).as_str());
+            then.status(200).body(page);
+        });
+        assert!(fetcher.get_input(4).is_err());
+        mock.assert();
+        assert!(!context.year_dir().join("04").exists());
+    }
+
+    #[test]
+    fn inputs_are_stored_per_year() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher().for_year(2022);
+        assert_eq!(fetcher.year(), 2022);
+        let mock = context.server.mock(|when, then| {
+            when.method(GET)
+                .path(url_path(2022, 1).as_str())
+                .header("Cookie", format!("session={}", context.session_token));
+            then.status(200).body("1000\n2000\n");
+        });
+        assert_eq!(fetcher.get_input(1).unwrap(), "1000\n2000\n");
+        mock.assert();
+        let input_path = context.input_dir.path().join("2022/01");
+        assert_eq!(std::fs::read_to_string(input_path).unwrap(), "1000\n2000\n");
+        assert_eq!(
+            context.get_fetcher().get_input(1).unwrap(),
+            context.get_input(1)
+        );
+    }
+
+    #[test]
+    fn flat_layout_is_migrated_into_year_directory() {
+        let context = TestContext::create();
+        let dir = context.input_dir.path();
+        for name in ["01", "01.html", "25.meta.toml"] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        std::fs::create_dir(dir.join("guesses")).unwrap();
+        std::fs::write(dir.join(".last-request"), "").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let fetcher = context.get_fetcher();
+        // Every input is already in the year directory, so the old copy of day 1 is left alone.
+        assert_eq!(fetcher.migrate_flat_layout().unwrap(), 3);
+        assert_eq!(
+            std::fs::read_to_string(context.year_dir().join("01.html")).unwrap(),
+            "01.html"
+        );
+        assert!(context.year_dir().join("guesses").is_dir());
+        assert_eq!(fetcher.get_input(1).unwrap(), context.get_input(1));
+        for name in ["01", ".last-request", "notes.txt"] {
+            assert!(dir.join(name).exists());
+        }
+        assert_eq!(fetcher.migrate_flat_layout().unwrap(), 0);
+    }
+
+    #[test]
+    fn inputs_are_encrypted_at_rest() {
+        let context = TestContext::create();
+        let fetcher = context
+            .get_fetcher()
+            .with_store(SecureStore::for_tests("secret"));
+        context.delete_puzzle_input_file(1);
+        let mock = context.server_up_mock(1);
+        assert_eq!(fetcher.get_input(1).unwrap(), context.get_input(1));
+        mock.assert();
+        let input_path = context.year_dir().join("01");
+        assert!(is_encrypted(&std::fs::read(&input_path).unwrap()));
+        assert_eq!(fetcher.get_input(1).unwrap(), context.get_input(1));
+        assert_eq!(
+            fetcher.read_file(&input_path).unwrap(),
+            context.get_input(1)
+        );
+
+        let error = context.get_fetcher().get_input(1).unwrap_err().to_string();
+        assert!(error.contains("is encrypted"), "{}", error);
+        let other_key = context
+            .get_fetcher()
+            .with_store(SecureStore::for_tests("other"));
+        assert!(other_key.get_input(1).is_err());
+    }
+
+    #[test]
+    fn plain_cached_inputs_are_encrypted_in_place() {
+        let context = TestContext::create();
+        let fetcher = context
+            .get_fetcher()
+            .with_store(SecureStore::for_tests("secret"));
+        let input_path = context.year_dir().join("02");
+        assert!(!is_encrypted(&std::fs::read(&input_path).unwrap()));
+        assert_eq!(fetcher.get_input(2).unwrap(), context.get_input(2));
+        assert!(is_encrypted(&std::fs::read(&input_path).unwrap()));
+        assert_eq!(fetcher.get_input(2).unwrap(), context.get_input(2));
+    }
+
+    #[test]
+    fn encrypted_session_token_is_decrypted() {
+        let context = TestContext::create();
+        let store = SecureStore::for_tests("secret");
+        std::fs::write(
+            context.session_token_file.path(),
+            store.encrypt(context.session_token.as_bytes()).unwrap(),
+        )
+        .unwrap();
+        assert!(context.get_fetcher().get_session_token().is_err());
+        let fetcher = context.get_fetcher().with_store(store);
+        assert_eq!(fetcher.get_session_token().unwrap(), context.session_token);
+    }
+
     #[test]
     fn fetch_fails_when_all_sources_unavailable() {
         let context = TestContext::create();
@@ -121,7 +1110,7 @@ mod tests {
             let mock = context.server_down_mock(day);
             let result = fetcher.get_input(day);
             assert!(result.is_err());
-            mock.assert();
+            mock.assert_hits(ATTEMPTS);
         }
     }
 
@@ -155,13 +1144,14 @@ mod tests {
             context.server.base_url().as_str(),
             context.input_dir.path(),
             bad_cookie_file.path(),
-        );
+        )
+        .with_clock(TestContext::clock());
         for day in 1..=25 {
             context.delete_puzzle_input_file(day);
             let mock_with_correct_token = context.server_up_mock(day);
             let mock_with_invalid_token = context.server.mock(|when, then| {
                 when.method(GET)
-                    .path(url_path(day).as_str())
+                    .path(url_path(2023, day).as_str())
                     .header("Cookie", format!("session={}", bad_cookie));
                 then.status(400)
                     .body("Puzzle inputs differ by user.  Please log in to get your puzzle input.");
@@ -173,6 +1163,221 @@ mod tests {
         }
     }
 
+    #[test]
+    fn session_token_is_trimmed() {
+        let context = TestContext::create();
+        std::fs::write(
+            context.session_token_file.path(),
+            format!("  {}\r\n", context.session_token),
+        )
+        .unwrap();
+        let fetcher = context.get_fetcher();
+        context.delete_puzzle_input_file(1);
+        let mock = context.server_up_mock(1);
+        assert_eq!(fetcher.get_input(1).unwrap(), context.get_input(1));
+        mock.assert();
+    }
+
+    #[test]
+    fn fetch_fails_if_malformed_session_token() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        context.delete_puzzle_input_file(1);
+        for token in ["abc123", &"g".repeat(128)] {
+            std::fs::write(context.session_token_file.path(), token).unwrap();
+            let mock = context.server_up_mock(1);
+            let error = fetcher.get_input(1).unwrap_err().to_string();
+            assert!(error.starts_with("Invalid session token"), "{}", error);
+            assert!(!error.contains(token));
+            mock.assert_hits(0);
+        }
+    }
+
+    #[test]
+    fn session_token_is_read_from_first_available_source() {
+        let context = TestContext::create();
+        let empty_file = NamedTempFile::new().unwrap();
+        let fetcher = InputFetcher {
+            session_token_sources: vec![
+                TokenSource::Env("AOC_SESSION_UNSET_IN_TESTS".to_string()),
+                TokenSource::File(context.input_dir.path().join("missing")),
+                TokenSource::File(empty_file.path().to_path_buf()),
+                TokenSource::File(context.session_token_file.path().to_path_buf()),
+            ],
+            ..context.get_fetcher()
+        };
+        assert_eq!(fetcher.get_session_token().unwrap(), context.session_token);
+
+        let fetcher = InputFetcher {
+            session_token_sources: vec![TokenSource::Env("AOC_SESSION_UNSET_IN_TESTS".to_string())],
+            ..context.get_fetcher()
+        };
+        let error = fetcher.get_session_token().unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "No session token found (tried $AOC_SESSION_UNSET_IN_TESTS)"
+        );
+    }
+
+    #[test]
+    fn default_token_sources_are_in_order_of_precedence() {
+        let sources = default_token_sources(
+            Some(Path::new("session.txt")),
+            Some(Path::new("/home/elf/.config").to_path_buf()),
+        );
+        assert_eq!(
+            sources,
+            vec![
+                TokenSource::Env("AOC_SESSION".to_string()),
+                TokenSource::File("session.txt".into()),
+                TokenSource::File("/home/elf/.config/aoc/session".into()),
+                TokenSource::File("cookie.txt".into()),
+            ]
+        );
+        assert_eq!(default_token_sources(None, None).len(), 2);
+    }
+
+    #[test]
+    fn validate_session_token_requires_128_hex_digits() {
+        assert!(validate_session_token(&random_session_token()).is_ok());
+        assert!(validate_session_token(&"A".repeat(128)).is_ok());
+        assert!(validate_session_token(&"a".repeat(127)).is_err());
+        assert!(validate_session_token(&"z".repeat(128)).is_err());
+    }
+
+    #[test]
+    fn leaderboard_is_fetched_and_cached_for_fifteen_minutes() {
+        let context = TestContext::create();
+        let json = include_str!("../resources/leaderboard/private.json");
+        let mock = context.leaderboard_mock(200, json);
+        let (leaderboard, stale) = context.get_fetcher().get_leaderboard(1001).unwrap();
+        assert_eq!(leaderboard.members.len(), 4);
+        assert!(stale.is_none());
+        assert_eq!(
+            std::fs::read_to_string(context.year_dir().join("leaderboards/1001.json")).unwrap(),
+            json
+        );
+
+        // Polling again within fifteen minutes uses the cached copy.
+        let later = TestContext::clock();
+        later.sleep(chrono::Duration::minutes(14));
+        let fetcher = context.get_fetcher().with_clock(later);
+        assert_eq!(fetcher.get_leaderboard(1001).unwrap().0, leaderboard);
+        mock.assert_hits(1);
+
+        let later = TestContext::clock();
+        later.sleep(chrono::Duration::minutes(16));
+        let fetcher = context.get_fetcher().with_clock(later);
+        assert_eq!(fetcher.get_leaderboard(1001).unwrap().0, leaderboard);
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn stale_leaderboard_is_used_when_fetch_fails() {
+        let context = TestContext::create();
+        let mut mock =
+            context.leaderboard_mock(200, include_str!("../resources/leaderboard/private.json"));
+        let (leaderboard, _) = context.get_fetcher().get_leaderboard(1001).unwrap();
+        mock.delete();
+        let mock = context.leaderboard_mock(302, "");
+        let later = TestContext::clock();
+        later.sleep(chrono::Duration::hours(1));
+        let fetcher = context.get_fetcher().with_clock(later);
+        let (stale_leaderboard, error) = fetcher.get_leaderboard(1001).unwrap();
+        assert_eq!(stale_leaderboard, leaderboard);
+        assert!(error.is_some());
+        mock.assert();
+    }
+
+    #[test]
+    fn leaderboard_fetch_fails_without_cache() {
+        let context = TestContext::create();
+        let mock = context.leaderboard_mock(302, "");
+        assert!(context.get_fetcher().get_leaderboard(1001).is_err());
+        mock.assert();
+
+        // A response that is not a leaderboard is never cached.
+        let context = TestContext::create();
+        context.leaderboard_mock(200, "<!DOCTYPE html>");
+        assert!(context.get_fetcher().get_leaderboard(1001).is_err());
+        assert!(!context.year_dir().join("leaderboards/1001.json").exists());
+    }
+
+    const CALENDAR: &str = "<html><header><div class=\"user\">Elf</div></header><main>\
+        <pre class=\"calendar\">\
+        <a aria-label=\"Day 1, two stars\" href=\"/2023/day/1\" class=\"calendar-day1 calendar-verycomplete\">1 **</a>\
+        <a aria-label=\"Day 2, one star\" href=\"/2023/day/2\" class=\"calendar-day2 calendar-complete\">2 *</a>\
+        <a aria-label=\"Day 3\" href=\"/2023/day/3\" class=\"calendar-day3\">3</a>\
+        <span class=\"calendar-day4\">4</span>\
+        <a href=\"/2023/leaderboard\" class=\"calendar-link\">Leaderboard</a>\
+        </pre></main></html>";
+
+    #[test]
+    fn calendar_stars_are_read_per_day() {
+        let stars = calendar_stars(CALENDAR);
+        assert_eq!(
+            stars.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 1), (3, 0)]
+        );
+    }
+
+    #[test]
+    fn get_stars_reads_calendar() {
+        let context = TestContext::create();
+        let mock = context.server.mock(|when, then| {
+            when.method(GET)
+                .path(calendar_url_path(2023))
+                .header("Cookie", format!("session={}", context.session_token));
+            then.status(200).body(CALENDAR);
+        });
+        let stars = context.get_fetcher().get_stars().unwrap();
+        assert_eq!(stars.get(&1), Some(&2));
+        mock.assert();
+    }
+
+    #[test]
+    fn get_stars_fails_when_logged_out() {
+        let context = TestContext::create();
+        context.server.mock(|when, then| {
+            when.method(GET).path(calendar_url_path(2023));
+            then.status(200)
+                .body(CALENDAR.replace("<div class=\"user\">Elf</div>", ""));
+        });
+        assert!(context.get_fetcher().get_stars().is_err());
+    }
+
+    #[test]
+    fn check_session_token_reports_user() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.server.mock(|when, then| {
+            when.method(GET)
+                .path(SETTINGS_URL_PATH)
+                .header("Cookie", format!("session={}", context.session_token));
+            then.status(200).body(
+                "<html><header><div class=\"user\">Elf <span class=\"star-count\">50*</span>\
+                 </div></header></html>",
+            );
+        });
+        assert_eq!(
+            fetcher.check_session_token().unwrap(),
+            Some("Elf".to_string())
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn check_session_token_fails_if_expired() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.server.mock(|when, then| {
+            when.method(GET).path(SETTINGS_URL_PATH);
+            then.status(302).header("Location", "/auth/login");
+        });
+        assert!(fetcher.check_session_token().is_err());
+        mock.assert();
+    }
+
     #[test]
     fn fetch_fails_if_puzzle_requested_early() {
         let context = TestContext::create();
@@ -181,7 +1386,7 @@ mod tests {
             context.delete_puzzle_input_file(day);
             let mock = context.server.mock(|when, then| {
                 when.method(GET)
-                    .path(url_path(day).as_str())
+                    .path(url_path(2023, day).as_str())
                     .header("Cookie", format!("session={}", context.session_token));
                 then.status(404)
                     .body("Please don't repeatedly request this endpoint before it unlocks! \
@@ -194,6 +1399,311 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fetch_refuses_locked_day() {
+        let context = TestContext::create();
+        let now = unlock_time(2023, 5).unwrap() - chrono::Duration::seconds(3 * 3600 + 61);
+        let fetcher = context.get_fetcher().with_clock(FixedClock::new(now));
+        context.delete_puzzle_input_file(5);
+        let mock = context.server_up_mock(5);
+        assert_eq!(
+            fetcher.time_until_unlock(5).unwrap(),
+            Some(chrono::Duration::seconds(3 * 3600 + 61))
+        );
+        assert_eq!(fetcher.time_until_unlock(4).unwrap(), None);
+        let error = fetcher.get_input(5).unwrap_err().to_string();
+        assert_eq!(error, "Day 5 unlocks in 3h 1m 1s");
+        assert!(fetcher.get_description(5).is_err());
+        assert!(fetcher.submit_answer(5, 1, "35").is_err());
+        mock.assert_hits(0);
+
+        context.delete_puzzle_input_file(4);
+        let mock = context.server_up_mock(4);
+        assert_eq!(fetcher.get_input(4).unwrap(), context.get_input(4));
+        mock.assert();
+    }
+
+    #[test]
+    fn fetch_refuses_days_outside_event() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        for day in [0, 26, 32, u8::MAX] {
+            let error = fetcher.get_input(day).unwrap_err().to_string();
+            assert_eq!(
+                error,
+                format!("There is no day {} (days run from 1 to 25)", day)
+            );
+            assert!(fetcher.get_page(day).is_err());
+            assert!(fetcher.submit_answer(day, 1, "35").is_err());
+        }
+    }
+
+    #[test]
+    fn fetch_waits_for_locked_day_to_unlock() {
+        let context = TestContext::create();
+        let now = unlock_time(2023, 5).unwrap() - chrono::Duration::minutes(10);
+        let fetcher = context
+            .get_fetcher()
+            .with_clock(FixedClock::new(now))
+            .wait_for_unlock(true);
+        context.delete_puzzle_input_file(5);
+        let mock = context.server_up_mock(5);
+        assert_eq!(fetcher.get_input(5).unwrap(), context.get_input(5));
+        assert_eq!(Some(fetcher.clock.now()), unlock_time(2023, 5));
+        assert_eq!(fetcher.time_until_unlock(5).unwrap(), None);
+        mock.assert();
+    }
+
+    #[test]
+    fn submit_reads_outcome_from_response() {
+        let cases = [
+            (
+                "<article><p>That's the right answer!  You are <span class=\"day-success\">one \
+                 gold star</span> closer to restoring snow operations.</p></article>",
+                SubmitOutcome::Correct,
+            ),
+            (
+                "<article><p>That's not the right answer; your answer is too high.  If you're \
+                 stuck, make sure you're using the full input data.</p></article>",
+                SubmitOutcome::TooHigh,
+            ),
+            (
+                "<article><p>That's not the right answer; your answer is too low.</p></article>",
+                SubmitOutcome::TooLow,
+            ),
+            (
+                "<article><p>That's not the right answer.  If you're stuck, there are some \
+                 general tips on the about page.</p></article>",
+                SubmitOutcome::Wrong,
+            ),
+            (
+                "<article><p>You gave an answer too recently; you have to wait after submitting \
+                 an answer before trying again.  You have 34s left to wait.</p></article>",
+                SubmitOutcome::RateLimited {
+                    wait: Duration::from_secs(34),
+                },
+            ),
+            (
+                "<article><p>You gave an answer too recently; you have to wait after submitting \
+                 an answer before trying again.  You have 4m 12s left to wait.</p></article>",
+                SubmitOutcome::RateLimited {
+                    wait: Duration::from_secs(252),
+                },
+            ),
+            (
+                "<article><p>You don't seem to be solving the right level.  Did you already \
+                 complete it?</p></article>",
+                SubmitOutcome::AlreadySolved,
+            ),
+        ];
+        for (i, (html, expected)) in cases.into_iter().enumerate() {
+            let context = TestContext::create();
+            let answer = format!("{}", 1000 + i);
+            let mock = context.submit_mock(1, 2, &answer, 200, html);
+            let outcome = context.get_fetcher().submit_answer(1, 2, &answer).unwrap();
+            assert_eq!(outcome, expected);
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn submit_never_resubmits_wrong_answer() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.submit_mock(
+            3,
+            1,
+            "4361",
+            200,
+            "<article><p>That's not the right answer; your answer is too low.</p></article>",
+        );
+        assert_eq!(
+            fetcher.submit_answer(3, 1, "4361").unwrap(),
+            SubmitOutcome::TooLow
+        );
+        assert_eq!(
+            fetcher.submit_answer(3, 1, "4361").unwrap(),
+            SubmitOutcome::TooLow
+        );
+        mock.assert_hits(1);
+
+        // The guess is only recorded for the part it was made for.
+        let other_part = context.submit_mock(
+            3,
+            2,
+            "4361",
+            200,
+            "<article><p>That's the right answer!</p></article>",
+        );
+        assert_eq!(
+            fetcher.submit_answer(3, 2, "4361").unwrap(),
+            SubmitOutcome::Correct
+        );
+        other_part.assert();
+    }
+
+    #[test]
+    fn submit_rejects_answers_with_tabs_or_line_breaks() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.submit_mock(3, 1, "4361\tToo high", 200, "");
+        for answer in ["4361\tToo high", "4361\n", "EFEH\r\nZZ"] {
+            let error = fetcher.submit_answer(3, 1, answer).unwrap_err().to_string();
+            assert!(error.contains("tab or line break"), "{}", error);
+        }
+        mock.assert_hits(0);
+        assert!(!context.year_dir().join("guesses").exists());
+    }
+
+    #[test]
+    fn submit_does_not_record_rate_limited_answer() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.submit_mock(
+            5,
+            1,
+            "35",
+            200,
+            "<article><p>You gave an answer too recently.  You have 10s left to wait.</p></article>",
+        );
+        fetcher.submit_answer(5, 1, "35").unwrap();
+        fetcher.submit_answer(5, 1, "35").unwrap();
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn submit_fails_on_unexpected_response() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.submit_mock(7, 1, "6440", 200, "<html>Something else</html>");
+        assert!(fetcher.submit_answer(7, 1, "6440").is_err());
+        mock.assert();
+        let mock = context.submit_mock(7, 2, "5905", 500, "");
+        assert!(fetcher.submit_answer(7, 2, "5905").is_err());
+        mock.assert_hits(ATTEMPTS);
+    }
+
+    #[test]
+    fn submit_fails_if_missing_session_token() {
+        let context = TestContext::create();
+        let fetcher = InputFetcher::create_custom(
+            context.server.base_url().as_str(),
+            context.input_dir.path(),
+            Path::new("missing_cookie.txt"),
+        );
+        let mock = context.submit_mock(1, 1, "142", 200, "That's the right answer!");
+        assert!(fetcher.submit_answer(1, 1, "142").is_err());
+        mock.assert_hits(0);
+    }
+
+    const PART_1_PAGE: &str = "<html><body><main>\
+        <article class=\"day-desc\"><h2>--- Day 1: Trebuchet?! ---</h2>\
+        <p>Something is wrong with global snow production.</p>\
+        <pre><code>1abc2\npqr3stu8vwx</code></pre></article>\
+        <p>Your puzzle answer was <code>55002</code>.</p></main></body></html>";
+
+    const BOTH_PARTS_PAGE: &str = "<html><body><main>\
+        <article class=\"day-desc\"><h2>--- Day 1: Trebuchet?! ---</h2>\
+        <p>Something is wrong with global snow production.</p></article>\
+        <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+        <p>Some of the digits are actually <em>spelled out with letters</em>.</p></article>\
+        </main></body></html>";
+
+    #[test]
+    fn description_is_converted_to_markdown_and_cached() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.description_mock(1, 200, BOTH_PARTS_PAGE);
+        let markdown = fetcher.get_description(1).unwrap();
+        assert!(markdown.contains("Day 1: Trebuchet?!"));
+        assert!(markdown.contains("Something is wrong with global snow production."));
+        assert!(markdown.contains("Part Two"));
+        assert!(markdown.contains("*spelled out with letters*"));
+        assert!(!markdown.contains("<p>"));
+        let dir = context.year_dir();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("01.html")).unwrap(),
+            BOTH_PARTS_PAGE
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("01.md")).unwrap(),
+            markdown
+        );
+
+        // Both parts are cached, so the page is not fetched again.
+        assert_eq!(fetcher.get_description(1).unwrap(), markdown);
+        mock.assert_hits(1);
+    }
+
+    #[test]
+    fn description_is_fetched_again_until_part_2_is_unlocked() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mut mock = context.description_mock(1, 200, PART_1_PAGE);
+        let markdown = fetcher.get_description(1).unwrap();
+        assert!(markdown.contains("1abc2"));
+        assert!(!markdown.contains("Part Two"));
+        assert!(!markdown.contains("55002"));
+        // The page without part 2 is used for a while before it is fetched again.
+        assert_eq!(fetcher.get_description(1).unwrap(), markdown);
+        mock.assert_hits(1);
+
+        let later = TestContext::clock();
+        later.sleep(chrono::Duration::minutes(61));
+        assert_eq!(
+            context
+                .get_fetcher()
+                .with_clock(later)
+                .get_description(1)
+                .unwrap(),
+            markdown
+        );
+        mock.assert_hits(2);
+        mock.delete();
+
+        // Solving part 1 unlocks part 2, so the page is fetched again straight away.
+        mock = context.description_mock(1, 200, BOTH_PARTS_PAGE);
+        let submit_mock = context.submit_mock(
+            1,
+            1,
+            "142",
+            200,
+            "<article><p>That's the right answer!</p></article>",
+        );
+        assert_eq!(
+            fetcher.submit_answer(1, 1, "142").unwrap(),
+            SubmitOutcome::Correct
+        );
+        submit_mock.assert();
+        assert!(fetcher.get_description(1).unwrap().contains("Part Two"));
+        mock.assert();
+    }
+
+    #[test]
+    fn description_falls_back_to_cached_part_1() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        std::fs::write(context.year_dir().join("01.html"), PART_1_PAGE).unwrap();
+        let mock = context.description_mock(1, 500, "");
+        assert!(fetcher
+            .get_description(1)
+            .unwrap()
+            .contains("global snow production"));
+        mock.assert_hits(ATTEMPTS);
+    }
+
+    #[test]
+    fn description_fails_without_cache_or_remote_access() {
+        let context = TestContext::create();
+        let fetcher = context.get_fetcher();
+        let mock = context.description_mock(1, 500, "");
+        assert!(fetcher.get_description(1).is_err());
+        mock.assert_hits(ATTEMPTS);
+        let mock = context.description_mock(2, 200, "<html><body>Not a puzzle</body></html>");
+        assert!(fetcher.get_description(2).is_err());
+        mock.assert();
+    }
+
     struct TestContext {
         inputs: Vec<String>,
         input_dir: TempDir,
@@ -206,8 +1716,9 @@ mod tests {
         pub fn create() -> Self {
             let inputs: Vec<String> = (1..=25).map(|_| random_puzzle()).collect();
             let input_dir = TempDir::new().unwrap();
+            std::fs::create_dir(input_dir.path().join("2023")).unwrap();
             for day in 1..=25 {
-                let input_file_path = input_dir.path().join(format!("{:02}", day));
+                let input_file_path = input_dir.path().join("2023").join(format!("{:02}", day));
                 std::fs::write(&input_file_path, &inputs[day - 1]).unwrap();
             }
             let session_token = random_session_token();
@@ -226,7 +1737,7 @@ mod tests {
         pub fn server_up_mock(&self, day: u8) -> Mock {
             self.server.mock(|when, then| {
                 when.method(GET)
-                    .path(url_path(day).as_str())
+                    .path(url_path(2023, day).as_str())
                     .header("Cookie", format!("session={}", self.session_token));
                 then.status(200).body(self.get_input(day));
             })
@@ -235,12 +1746,48 @@ mod tests {
         pub fn server_down_mock(&self, day: u8) -> Mock {
             self.server.mock(|when, then| {
                 when.method(GET)
-                    .path(url_path(day).as_str())
+                    .path(url_path(2023, day).as_str())
                     .header("Cookie", format!("session={}", self.session_token));
                 then.status(501);
             })
         }
 
+        pub fn description_mock(&self, day: u8, status: u16, body: &str) -> Mock {
+            self.server.mock(|when, then| {
+                when.method(GET)
+                    .path(description_url_path(2023, day).as_str())
+                    .header("Cookie", format!("session={}", self.session_token));
+                then.status(status).body(body);
+            })
+        }
+
+        pub fn submit_mock(
+            &self,
+            day: u8,
+            part: u8,
+            answer: &str,
+            status: u16,
+            body: &str,
+        ) -> Mock {
+            self.server.mock(|when, then| {
+                when.method(POST)
+                    .path(answer_url_path(2023, day).as_str())
+                    .header("Cookie", format!("session={}", self.session_token))
+                    .x_www_form_urlencoded_tuple("level", part.to_string())
+                    .x_www_form_urlencoded_tuple("answer", answer);
+                then.status(status).body(body);
+            })
+        }
+
+        pub fn leaderboard_mock(&self, status: u16, body: &str) -> Mock {
+            self.server.mock(|when, then| {
+                when.method(GET)
+                    .path(leaderboard_url_path(2023, 1001))
+                    .header("Cookie", format!("session={}", self.session_token));
+                then.status(status).body(body);
+            })
+        }
+
         pub fn get_input(&self, day: u8) -> &str {
             &self.inputs[day as usize - 1]
         }
@@ -251,10 +1798,21 @@ mod tests {
                 self.input_dir.path(),
                 self.session_token_file.path(),
             )
+            .with_clock(Self::clock())
+        }
+
+        /// Returns a clock set after the event, which skips the waits between requests.
+        pub fn clock() -> FixedClock {
+            FixedClock::new(unlock_time(2023, 25).unwrap() + chrono::Duration::days(30))
+        }
+
+        /// Returns the directory where the fetcher stores the files of 2023.
+        pub fn year_dir(&self) -> PathBuf {
+            self.input_dir.path().join("2023")
         }
 
         pub fn delete_puzzle_input_file(&self, day: u8) {
-            let input_file_path = self.input_dir.path().join(format!("{:02}", day));
+            let input_file_path = self.year_dir().join(format!("{:02}", day));
             std::fs::remove_file(input_file_path).unwrap();
         }
     }
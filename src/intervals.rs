@@ -0,0 +1,456 @@
+use std::ops::Range;
+
+/// A set of integers, stored as sorted, disjoint and non-adjacent half-open ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every integer in the range to the set.
+    pub fn insert(&mut self, range: Range<i64>) {
+        if !range.is_empty() {
+            self.ranges.push(range);
+            self.normalize();
+        }
+    }
+
+    /// Returns the ranges of the set, in increasing order.
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.start.abs_diff(range.end))
+            .sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges
+            .get(i)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    /// Returns the smallest integer in the set.
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let (mut i, mut j) = (0, 0);
+        let mut ranges = Vec::new();
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Sorts the ranges and merges any that overlap or touch.
+    fn normalize(&mut self) {
+        self.ranges.sort_unstable_by_key(|range| range.start);
+        let mut merged: Vec<Range<i64>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        self.ranges = merged;
+    }
+}
+
+impl FromIterator<Range<i64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = RangeSet {
+            ranges: iter.into_iter().filter(|range| !range.is_empty()).collect(),
+        };
+        set.normalize();
+        set
+    }
+}
+
+/// A map from integers to integers that adds a fixed offset to each of a number of disjoint
+/// ranges, and leaves every other integer as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    /// Sorted, disjoint ranges with a non-zero offset. Adjacent ranges have different offsets.
+    pieces: Vec<(Range<i64>, i64)>,
+}
+
+impl OffsetMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the integers in the range by the offset, replacing whatever they were mapped by
+    /// before.
+    pub fn insert(&mut self, range: Range<i64>, offset: i64) {
+        if range.is_empty() {
+            return;
+        }
+        let mut pieces = Vec::with_capacity(self.pieces.len() + 2);
+        for (piece, piece_offset) in self.pieces.drain(..) {
+            if piece.start < range.start {
+                pieces.push((piece.start..piece.end.min(range.start), piece_offset));
+            }
+            if piece.end > range.end {
+                pieces.push((piece.start.max(range.end)..piece.end, piece_offset));
+            }
+        }
+        pieces.push((range, offset));
+        self.pieces = pieces;
+        self.normalize();
+    }
+
+    /// Returns the ranges that are moved and their offsets, in increasing order.
+    pub fn pieces(&self) -> &[(Range<i64>, i64)] {
+        &self.pieces
+    }
+
+    pub fn get(&self, value: i64) -> i64 {
+        let i = self.pieces.partition_point(|(range, _)| range.end <= value);
+        match self.pieces.get(i) {
+            Some((range, offset)) if range.contains(&value) => value + offset,
+            _ => value,
+        }
+    }
+
+    /// Maps every integer in the set.
+    pub fn apply(&self, set: &RangeSet) -> RangeSet {
+        set.ranges()
+            .iter()
+            .flat_map(|range| self.segments(range.clone()))
+            .map(|(range, offset)| range.start + offset..range.end + offset)
+            .collect()
+    }
+
+    /// Returns the map that applies this map and then the other.
+    pub fn then(&self, other: &OffsetMap) -> OffsetMap {
+        // Every integer outside both maps' ranges is left as it is by both.
+        let domain: RangeSet = self
+            .pieces
+            .iter()
+            .chain(&other.pieces)
+            .map(|(range, _)| range.clone())
+            .collect();
+        let mut pieces = Vec::new();
+        for range in domain.ranges() {
+            for (first, offset) in self.segments(range.clone()) {
+                let image = first.start + offset..first.end + offset;
+                for (second, other_offset) in other.segments(image) {
+                    pieces.push((
+                        second.start - offset..second.end - offset,
+                        offset + other_offset,
+                    ));
+                }
+            }
+        }
+        let mut map = OffsetMap { pieces };
+        map.normalize();
+        map
+    }
+
+    /// Splits the range into the parts with the same offset, including parts that are not moved.
+    fn segments(&self, range: Range<i64>) -> Vec<(Range<i64>, i64)> {
+        let mut segments = Vec::new();
+        let mut start = range.start;
+        let first = self.pieces.partition_point(|(piece, _)| piece.end <= start);
+        for (piece, offset) in &self.pieces[first..] {
+            if piece.start >= range.end {
+                break;
+            }
+            if start < piece.start {
+                segments.push((start..piece.start, 0));
+            }
+            let end = piece.end.min(range.end);
+            segments.push((start.max(piece.start)..end, *offset));
+            start = end;
+        }
+        if start < range.end {
+            segments.push((start..range.end, 0));
+        }
+        segments
+    }
+
+    /// Sorts the pieces, drops those that do not move anything and merges adjacent pieces with
+    /// the same offset.
+    fn normalize(&mut self) {
+        self.pieces.sort_unstable_by_key(|(range, _)| range.start);
+        let mut merged: Vec<(Range<i64>, i64)> = Vec::with_capacity(self.pieces.len());
+        for (range, offset) in self.pieces.drain(..) {
+            if offset == 0 || range.is_empty() {
+                continue;
+            }
+            match merged.last_mut() {
+                Some((last, last_offset)) if last.end == range.start && *last_offset == offset => {
+                    last.end = range.end
+                }
+                _ => merged.push((range, offset)),
+            }
+        }
+        self.pieces = merged;
+    }
+}
+
+/// An N-dimensional box of integer points, with a half-open range along each axis.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperrectangle<const N: usize> {
+    pub ranges: [Range<i64>; N],
+}
+
+impl<const N: usize> Hyperrectangle<N> {
+    pub fn new(ranges: [Range<i64>; N]) -> Self {
+        Self { ranges }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.iter().any(|range| range.is_empty())
+    }
+
+    /// Returns the number of points in the box.
+    pub fn volume(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.ranges
+            .iter()
+            .map(|range| range.start.abs_diff(range.end) as u128)
+            .product()
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.ranges
+            .iter()
+            .zip(point)
+            .all(|(range, value)| range.contains(value))
+    }
+
+    /// Splits the box along an axis into the points below the value and the points at or above
+    /// it. Either part may be empty.
+    pub fn split(&self, axis: usize, at: i64) -> (Self, Self) {
+        let (mut below, mut above) = (self.clone(), self.clone());
+        let range = &self.ranges[axis];
+        below.ranges[axis] = range.start..at.clamp(range.start, range.end);
+        above.ranges[axis] = at.clamp(range.start, range.end)..range.end;
+        (below, above)
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let intersection = Self {
+            ranges: std::array::from_fn(|axis| {
+                let (a, b) = (&self.ranges[axis], &other.ranges[axis]);
+                a.start.max(b.start)..a.end.min(b.end)
+            }),
+        };
+        (!intersection.is_empty()).then_some(intersection)
+    }
+
+    /// Returns disjoint boxes covering the points of this box that are not in the other.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        if self.intersect(other).is_none() {
+            return vec![self.clone()];
+        }
+        let mut pieces = Vec::new();
+        let mut rest = self.clone();
+        for axis in 0..N {
+            let (below, middle) = rest.split(axis, other.ranges[axis].start);
+            let (middle, above) = middle.split(axis, other.ranges[axis].end);
+            pieces.extend([below, above].into_iter().filter(|piece| !piece.is_empty()));
+            rest = middle;
+        }
+        pieces
+    }
+}
+
+/// A set of integer points in N dimensions, stored as disjoint boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoxSet<const N: usize> {
+    boxes: Vec<Hyperrectangle<N>>,
+}
+
+impl<const N: usize> Default for BoxSet<N> {
+    fn default() -> Self {
+        Self { boxes: Vec::new() }
+    }
+}
+
+impl<const N: usize> BoxSet<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every point in the box to the set.
+    pub fn insert(&mut self, new: Hyperrectangle<N>) {
+        let mut pieces = vec![new];
+        for existing in &self.boxes {
+            pieces = pieces
+                .iter()
+                .flat_map(|piece| piece.difference(existing))
+                .collect();
+        }
+        self.boxes
+            .extend(pieces.into_iter().filter(|piece| !piece.is_empty()));
+    }
+
+    /// Returns the disjoint boxes that make up the set.
+    pub fn boxes(&self) -> &[Hyperrectangle<N>] {
+        &self.boxes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// Returns the number of points in the set.
+    pub fn volume(&self) -> u128 {
+        self.boxes.iter().map(Hyperrectangle::volume).sum()
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.boxes.iter().any(|b| b.contains(point))
+    }
+
+    /// Splits the set along an axis into the points below the value and the points at or above
+    /// it.
+    pub fn split(&self, axis: usize, at: i64) -> (Self, Self) {
+        let (mut below, mut above) = (Self::new(), Self::new());
+        for b in &self.boxes {
+            let (b_below, b_above) = b.split(axis, at);
+            if !b_below.is_empty() {
+                below.boxes.push(b_below);
+            }
+            if !b_above.is_empty() {
+                above.boxes.push(b_above);
+            }
+        }
+        (below, above)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for b in &other.boxes {
+            union.insert(b.clone());
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            boxes: self
+                .boxes
+                .iter()
+                .flat_map(|a| other.boxes.iter().filter_map(|b| a.intersect(b)))
+                .collect(),
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<Hyperrectangle<N>> for BoxSet<N> {
+    fn from_iter<I: IntoIterator<Item = Hyperrectangle<N>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for b in iter {
+            set.insert(b);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_sets_merge_overlapping_and_adjacent_ranges() {
+        let set: RangeSet = [5..8, 0..2, 1..3, 3..4, 10..10].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..4, 5..8]);
+        assert_eq!(set.len(), 7);
+        assert!(set.contains(3) && !set.contains(4) && set.contains(7));
+        assert_eq!(set.min(), Some(0));
+
+        let other: RangeSet = [2..6, 7..20].into_iter().collect();
+        assert_eq!(set.intersection(&other).ranges(), &[2..4, 5..6, 7..8]);
+        assert_eq!(set.union(&other).ranges(), std::slice::from_ref(&(0..20)));
+    }
+
+    #[test]
+    fn offset_maps_apply_and_compose() {
+        let mut first = OffsetMap::new();
+        first.insert(0..10, 100);
+        first.insert(5..15, -5);
+        assert_eq!(first.pieces(), &[(0..5, 100), (5..15, -5)]);
+        assert_eq!((first.get(4), first.get(5), first.get(20)), (104, 0, 20));
+
+        let mut second = OffsetMap::new();
+        second.insert(100..103, 1000);
+        second.insert(8..12, 1);
+        let composed = first.then(&second);
+        for value in -5..25 {
+            assert_eq!(composed.get(value), second.get(first.get(value)));
+        }
+
+        let seeds: RangeSet = [0..2, 13..20].into_iter().collect();
+        assert_eq!(
+            composed.apply(&seeds).ranges(),
+            &[9..11, 15..20, 1100..1102]
+        );
+    }
+
+    #[test]
+    fn boxes_split_and_subtract() {
+        let cube = Hyperrectangle::new([0..4, 0..4, 0..4]);
+        assert_eq!(cube.volume(), 64);
+        let (below, above) = cube.split(1, 1);
+        assert_eq!((below.volume(), above.volume()), (16, 48));
+        assert!(cube.split(0, 10).1.is_empty());
+
+        let corner = Hyperrectangle::new([2..6, 2..6, 2..6]);
+        assert_eq!(cube.intersect(&corner).unwrap().volume(), 8);
+        let difference = cube.difference(&corner);
+        assert_eq!(
+            difference.iter().map(Hyperrectangle::volume).sum::<u128>(),
+            56
+        );
+        assert!(difference.iter().all(|piece| !piece.contains(&[3, 3, 3])));
+    }
+
+    #[test]
+    fn box_sets_have_exact_volume() {
+        let a = Hyperrectangle::new([0..4, 0..4]);
+        let b = Hyperrectangle::new([2..6, 2..6]);
+        let set: BoxSet<2> = [a.clone(), b.clone()].into_iter().collect();
+        assert_eq!(set.volume(), 28);
+        assert!(set.contains(&[5, 5]) && !set.contains(&[5, 0]));
+
+        let only_a: BoxSet<2> = [a].into_iter().collect();
+        let only_b: BoxSet<2> = [b].into_iter().collect();
+        assert_eq!(only_a.union(&only_b), set);
+        assert_eq!(only_a.intersection(&only_b).volume(), 4);
+        let (left, right) = set.split(0, 3);
+        assert_eq!(left.volume() + right.volume(), 28);
+        assert_eq!(left.volume(), 14);
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// How long a fetched leaderboard is used before it is fetched again. The website asks that
+/// private leaderboards are polled no more often than this.
+pub fn refresh_interval() -> Duration {
+    Duration::minutes(15)
+}
+
+/// A private leaderboard, as returned by the website's JSON API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    /// The year of the event, such as "2023".
+    pub event: String,
+    pub owner_id: u64,
+    /// The members, by their ID.
+    pub members: HashMap<String, Member>,
+}
+
+/// A member of a private leaderboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    /// The member's display name, unset for anonymous users.
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u32,
+    #[serde(default)]
+    pub global_score: u32,
+    /// When the member last got a star, as a Unix timestamp (0 if they have none).
+    #[serde(default)]
+    pub last_star_ts: i64,
+    /// The stars the member has got, by day and then part.
+    #[serde(default)]
+    pub completion_day_level: BTreeMap<u8, BTreeMap<u8, Star>>,
+}
+
+/// A star got by a member.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Star {
+    /// When the star was got, as a Unix timestamp.
+    pub get_star_ts: i64,
+    #[serde(default)]
+    pub star_index: u64,
+}
+
+impl Leaderboard {
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the members ordered as the website ranks them: by local score, then by who got
+    /// their last star first, then by ID.
+    pub fn standings(&self) -> Vec<&Member> {
+        let mut members: Vec<&Member> = self.members.values().collect();
+        members.sort_by_key(|member| {
+            (
+                std::cmp::Reverse(member.local_score),
+                member.last_star_ts,
+                member.id,
+            )
+        });
+        members
+    }
+
+    /// Returns the days on which any member has got a star, in order.
+    pub fn days(&self) -> Vec<u8> {
+        let mut days: Vec<u8> = self
+            .members
+            .values()
+            .flat_map(|member| member.completion_day_level.keys().copied())
+            .collect();
+        days.sort_unstable();
+        days.dedup();
+        days
+    }
+}
+
+impl Member {
+    /// Returns the member's name, or how the website refers to them if they are anonymous.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// Returns when the member got the star for the given part of a day, if they have.
+    pub fn star_time(&self, day: u8, part: u8) -> Option<DateTime<Utc>> {
+        let star = self.completion_day_level.get(&day)?.get(&part)?;
+        DateTime::from_timestamp(star.get_star_ts, 0)
+    }
+
+    /// Returns how long the member took to get the second star of a day after the first, if they
+    /// have both.
+    pub fn delta(&self, day: u8) -> Option<Duration> {
+        Some(self.star_time(day, 2)? - self.star_time(day, 1)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixture() -> Leaderboard {
+        Leaderboard::parse(include_str!("../resources/leaderboard/private.json")).unwrap()
+    }
+
+    #[test]
+    fn parses_recorded_response() {
+        let leaderboard = fixture();
+        assert_eq!(leaderboard.event, "2023");
+        assert_eq!(leaderboard.owner_id, 1001);
+        assert_eq!(leaderboard.members.len(), 4);
+        let member = &leaderboard.members["1001"];
+        assert_eq!(member.name.as_deref(), Some("Olivia"));
+        assert_eq!(member.stars, 4);
+        assert_eq!(member.completion_day_level[&2][&2].star_index, 160);
+        assert_eq!(leaderboard.days(), vec![1, 2]);
+    }
+
+    #[test]
+    fn standings_are_ranked_by_local_score() {
+        let leaderboard = fixture();
+        let names: Vec<String> = leaderboard
+            .standings()
+            .iter()
+            .map(|member| member.display_name())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Olivia", "Sam", "(anonymous user #3003)", "Idle"]
+        );
+    }
+
+    #[test]
+    fn star_times_and_deltas() {
+        let leaderboard = fixture();
+        let member = &leaderboard.members["2002"];
+        assert_eq!(
+            member.star_time(1, 1),
+            Some(Utc.with_ymd_and_hms(2023, 12, 1, 5, 2, 0).unwrap())
+        );
+        assert_eq!(member.delta(1), Some(Duration::minutes(58)));
+        assert_eq!(member.star_time(2, 2), None);
+        assert_eq!(member.delta(2), None);
+        assert_eq!(leaderboard.members["4004"].star_time(1, 1), None);
+    }
+
+    #[test]
+    fn missing_optional_fields_are_defaulted() {
+        let json = r#"{"event":"2023","owner_id":1,"members":{"1":{"id":1,"name":"A","stars":0,"local_score":0}}}"#;
+        let leaderboard = Leaderboard::parse(json).unwrap();
+        assert!(leaderboard.members["1"].completion_day_level.is_empty());
+        assert!(Leaderboard::parse("<!DOCTYPE html>").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+//! Solutions to the Advent of Code 2023 puzzles.
+//!
+//! Each `dayNN` module provides a [`puzzle::Solver`] named `Day` along with its parsed model and
+//! the functions used to solve it. The [`registry`] maps day numbers to their solutions, and the
+//! [`input_fetcher::InputFetcher`] downloads and caches puzzle inputs. The [`examples`] from the
+//! puzzle descriptions are kept as fixtures that every registered puzzle is tested against.
+
+pub mod clock;
+pub mod cycles;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod examples;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod http_client;
+pub mod input_cache;
+pub mod input_fetcher;
+pub mod intervals;
+pub mod leaderboard;
+pub mod puzzle;
+pub mod registry;
+pub mod secure_store;
@@ -1,65 +1,71 @@
-use crate::input_fetcher::InputFetcher;
-use crate::puzzle::Puzzle;
+use crate::cli::{Cli, Command};
+use aoc2023::input_fetcher::InputFetcher;
+use aoc2023::registry;
+use aoc2023::secure_store::SecureStore;
+use clap::Parser;
+use std::process::ExitCode;
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod input_fetcher;
-mod puzzle;
+mod answers;
+mod bench;
+mod cli;
+mod progress;
+mod runner;
+mod standings;
+mod table;
+mod timing;
 
-fn main() {
-    let fetcher = InputFetcher::create();
-    let puzzles: Vec<Box<dyn Puzzle>> = vec![
-        day01::Day::create(fetcher.get_input(1).unwrap().as_str()),
-        day02::Day::create(fetcher.get_input(2).unwrap().as_str()),
-        day03::Day::create(fetcher.get_input(3).unwrap().as_str()),
-        day04::Day::create(fetcher.get_input(4).unwrap().as_str()),
-        day05::Day::create(fetcher.get_input(5).unwrap().as_str()),
-        day06::Day::create(fetcher.get_input(6).unwrap().as_str()),
-        day07::Day::create(fetcher.get_input(7).unwrap().as_str()),
-        day08::Day::create(fetcher.get_input(8).unwrap().as_str()),
-        day09::Day::create(fetcher.get_input(9).unwrap().as_str()),
-        day10::Day::create(fetcher.get_input(10).unwrap().as_str()),
-        day11::Day::create(fetcher.get_input(11).unwrap().as_str()),
-        day12::Day::create(fetcher.get_input(12).unwrap().as_str()),
-        day13::Day::create(fetcher.get_input(13).unwrap().as_str()),
-        day14::Day::create(fetcher.get_input(14).unwrap().as_str()),
-        day15::Day::create(fetcher.get_input(15).unwrap().as_str()),
-        day16::Day::create(fetcher.get_input(16).unwrap().as_str()),
-        day17::Day::create(fetcher.get_input(17).unwrap().as_str()),
-        day18::Day::create(fetcher.get_input(18).unwrap().as_str()),
-        day19::Day::create(fetcher.get_input(19).unwrap().as_str()),
-        day20::Day::create(fetcher.get_input(20).unwrap().as_str()),
-        day21::Day::create(fetcher.get_input(21).unwrap().as_str()),
-        day22::Day::create(fetcher.get_input(22).unwrap().as_str()),
-        day23::Day::create(fetcher.get_input(23).unwrap().as_str()),
-        day24::Day::create(fetcher.get_input(24).unwrap().as_str()),
-        day25::Day::create(fetcher.get_input(25).unwrap().as_str()),
-    ];
-    for (i, puzzle) in puzzles.iter().enumerate() {
-        println!("Day {:02} Part 1: {}", i + 1, puzzle.solve_part_1());
-        println!("Day {:02} Part 2: {}", i + 1, puzzle.solve_part_2());
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let year = match cli.year {
+        Some(year) => match registry::get_year(year) {
+            Some(year) => year,
+            None => {
+                eprintln!("No solutions are registered for {}", year);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => registry::latest_year(),
+    };
+    let store = match &cli.key_file {
+        Some(path) => SecureStore::from_key_file(path).map(Some),
+        None => SecureStore::from_env(),
+    };
+    let store = match store {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut fetcher = InputFetcher::create_with_session_file(cli.session_file.as_deref())
+        .for_year(year.year)
+        .wait_for_unlock(cli.wait_for_unlock);
+    if let Some(store) = store {
+        fetcher = fetcher.with_store(store);
+    }
+    match fetcher.migrate_flat_layout() {
+        Ok(0) => {}
+        Ok(moved) => eprintln!("Moved {} cached file(s) into their year's directory", moved),
+        Err(e) => eprintln!(
+            "Failed to move cached files into their year's directory: {}",
+            e
+        ),
+    }
+    let success = match cli.command() {
+        Command::Run(args) => runner::run(&args, year, &fetcher),
+        Command::Bench(args) => runner::bench(&args, year, &fetcher),
+        Command::Verify(args) => runner::verify(&args, year, &fetcher),
+        Command::Examples(args) => runner::examples(&args, year, &fetcher),
+        Command::CheckToken => runner::check_token(&fetcher),
+        Command::Refetch(args) => runner::refetch(&args, year, &fetcher),
+        Command::Leaderboard(args) => runner::leaderboard(&args, &fetcher),
+        Command::Progress(args) => runner::progress(&args, year, &fetcher),
+        Command::Encrypt(args) => runner::encrypt(&args, &fetcher),
+        Command::Decrypt(args) => runner::decrypt(&args, &fetcher),
+    };
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
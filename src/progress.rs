@@ -0,0 +1,204 @@
+use crate::answers::Manifest;
+use crate::table;
+use aoc2023::registry::Year;
+use std::collections::BTreeMap;
+
+/// The last day of an event, whose second part has no answer of its own.
+const LAST_DAY: u8 = 25;
+
+/// How far along a day is: the stars the website shows for it, whether it has a solution and how
+/// many of its answers are recorded in the manifest.
+#[derive(Debug, PartialEq)]
+pub struct DayProgress {
+    pub day: u8,
+    pub stars: u8,
+    pub solved: bool,
+    /// The parts with a known answer in the manifest.
+    pub verified: u8,
+    /// The parts that have an answer to verify.
+    pub parts: u8,
+}
+
+impl DayProgress {
+    /// Describes what is missing for the day, if anything.
+    pub fn status(&self) -> &'static str {
+        if self.stars > 0 && !self.solved {
+            "no solution"
+        } else if self.solved && self.stars < 2 {
+            "not submitted"
+        } else if self.solved && self.verified < self.parts {
+            "answers not recorded"
+        } else if self.stars == 2 {
+            "ok"
+        } else {
+            ""
+        }
+    }
+}
+
+/// Compares the stars shown on the website with the year's solutions and the manifest's answers,
+/// for every day that has unlocked or has a solution.
+pub fn compare(year: &Year, stars: &BTreeMap<u8, u8>, manifest: &Manifest) -> Vec<DayProgress> {
+    (1..=LAST_DAY)
+        .filter(|day| stars.contains_key(day) || year.get(*day).is_some())
+        .map(|day| {
+            let parts = if day == LAST_DAY { 1 } else { 2 };
+            DayProgress {
+                day,
+                stars: stars.get(&day).copied().unwrap_or(0),
+                solved: year.get(day).is_some(),
+                verified: (1..=parts)
+                    .filter(|&part| manifest.has_answer(year.year, day, part))
+                    .count() as u8,
+                parts,
+            }
+        })
+        .collect()
+}
+
+/// Renders a table of each day's progress followed by the totals.
+pub fn render(days: &[DayProgress]) -> String {
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .map(|day| {
+            vec![
+                format!("{:02}", day.day),
+                "*".repeat(day.stars as usize),
+                if day.solved { "yes" } else { "no" }.to_string(),
+                format!("{}/{}", day.verified, day.parts),
+                day.status().to_string(),
+            ]
+        })
+        .collect();
+    let table = table::render(&["Day", "Stars", "Solution", "Verified", "Status"], &rows);
+    format!(
+        "{}\n\nStars: {}, days completed: {}",
+        table,
+        total_stars(days),
+        days_completed(days)
+    )
+}
+
+fn total_stars(days: &[DayProgress]) -> u32 {
+    days.iter().map(|day| day.stars as u32).sum()
+}
+
+fn days_completed(days: &[DayProgress]) -> usize {
+    days.iter().filter(|day| day.stars == 2).count()
+}
+
+/// Returns the README with its star and completed-day badges set to the given progress. Fails if
+/// the README has neither badge.
+pub fn update_badges(readme: &str, days: &[DayProgress]) -> Result<String, String> {
+    let stars = format!(
+        "![Stars](https://img.shields.io/badge/gold%20stars%20⭐-{}-yellow)",
+        total_stars(days)
+    );
+    let completed = format!(
+        "![Days Completed](https://img.shields.io/badge/days%20completed-{}-green)",
+        days_completed(days)
+    );
+    let mut found = false;
+    let mut updated = String::with_capacity(readme.len());
+    for line in readme.split_inclusive('\n') {
+        let badge = if line.starts_with("![Stars](") {
+            &stars
+        } else if line.starts_with("![Days Completed](") {
+            &completed
+        } else {
+            updated.push_str(line);
+            continue;
+        };
+        found = true;
+        updated.push_str(badge);
+        // Keep the line ending as it was.
+        updated.push_str(&line[line.trim_end().len()..]);
+    }
+    if found {
+        Ok(updated)
+    } else {
+        Err("No star or completed-day badge found".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2023::registry;
+
+    fn manifest() -> Manifest {
+        Manifest::parse(
+            r#"
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 1
+            answer = "55002"
+
+            [[answer]]
+            year = 2023
+            day = 1
+            part = 2
+            answer = "55093"
+
+            [[answer]]
+            year = 2023
+            day = 2
+            part = 1
+            answer = "1734"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn compare_reports_what_is_missing() {
+        let year = registry::get_year(2023).unwrap();
+        let stars = BTreeMap::from([(1, 2), (2, 2), (3, 1)]);
+        let days = compare(year, &stars, &manifest());
+        assert_eq!(days.len(), 25);
+        assert_eq!(days[0].status(), "ok");
+        assert_eq!(
+            days[1],
+            DayProgress {
+                day: 2,
+                stars: 2,
+                solved: true,
+                verified: 1,
+                parts: 2,
+            }
+        );
+        assert_eq!(days[1].status(), "answers not recorded");
+        assert_eq!(days[2].status(), "not submitted");
+        assert_eq!(days[24].parts, 1);
+    }
+
+    #[test]
+    fn render_shows_totals() {
+        let year = registry::get_year(2023).unwrap();
+        let stars = BTreeMap::from([(1, 2), (2, 1)]);
+        let report = render(&compare(year, &stars, &manifest()));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Day  Stars  Solution  Verified         Status");
+        assert_eq!(lines[2], "01      **       yes       2/2             ok");
+        assert!(report.ends_with("Stars: 3, days completed: 1"));
+    }
+
+    #[test]
+    fn update_badges_replaces_badge_lines() {
+        let readme = "# Title\n\
+            ![Stars](https://img.shields.io/badge/gold%20stars%20⭐-50-yellow)\r\n\
+            ![Days Completed](https://img.shields.io/badge/days%20completed-25-green)\n\
+            Text\n";
+        let year = registry::get_year(2023).unwrap();
+        let days = compare(year, &BTreeMap::from([(1, 2), (2, 1)]), &manifest());
+        assert_eq!(
+            update_badges(readme, &days).unwrap(),
+            "# Title\n\
+            ![Stars](https://img.shields.io/badge/gold%20stars%20⭐-3-yellow)\r\n\
+            ![Days Completed](https://img.shields.io/badge/days%20completed-1-green)\n\
+            Text\n"
+        );
+        assert!(update_badges("# Title\n", &days).is_err());
+    }
+}
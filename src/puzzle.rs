@@ -1,4 +1,346 @@
-pub trait Puzzle {
-    fn solve_part_1(&self) -> String;
-    fn solve_part_2(&self) -> String;
-}
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// A puzzle whose input has been parsed, ready to solve either part.
+pub trait Puzzle {
+    fn solve_part_1(&self) -> Result<Answer, PuzzleError>;
+    fn solve_part_2(&self) -> Result<Answer, PuzzleError>;
+}
+
+/// The solution to a day, which parses the input once into a model shared by both parts.
+pub trait Solver {
+    type Model: 'static;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError>;
+    fn solve_part_1(model: &Self::Model) -> Result<Answer, PuzzleError>;
+    fn solve_part_2(model: &Self::Model) -> Result<Answer, PuzzleError>;
+
+    /// Parses the input into a puzzle that can be solved without knowing its model type.
+    fn create(input: &str) -> Result<Box<dyn Puzzle>, PuzzleError>
+    where
+        Self: Sized + 'static,
+    {
+        Ok(Box::new(Parsed::<Self> {
+            model: Self::parse(input)?,
+            solver: PhantomData,
+        }))
+    }
+}
+
+/// Adapts a solver and its parsed model to the object-safe [`Puzzle`] trait.
+struct Parsed<S: Solver> {
+    model: S::Model,
+    solver: PhantomData<fn() -> S>,
+}
+
+impl<S: Solver> Puzzle for Parsed<S> {
+    fn solve_part_1(&self) -> Result<Answer, PuzzleError> {
+        S::solve_part_1(&self.model)
+    }
+
+    fn solve_part_2(&self) -> Result<Answer, PuzzleError> {
+        S::solve_part_2(&self.model)
+    }
+}
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    /// An integer too large for `Int`.
+    BigInt(i128),
+    Text(String),
+    /// The part has nothing to solve, such as the second part of the last day.
+    NotApplicable,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::NotApplicable => write!(f, "n/a"),
+        }
+    }
+}
+
+/// Serializes numeric answers as numbers, text as a string and a missing answer as null.
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Int(n) => serializer.serialize_i64(*n),
+            Answer::BigInt(n) => serializer.serialize_i128(*n),
+            Answer::Text(text) => serializer.serialize_str(text),
+            Answer::NotApplicable => serializer.serialize_none(),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    match i64::try_from(n) {
+                        Ok(n) => Answer::Int(n),
+                        Err(_) => Answer::BigInt(n as i128),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, u32, i64, u64, usize, i128, u128);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+/// An error caused by a puzzle input that a solver could not handle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleError {
+    pub day: u8,
+    /// Where in the input the problem was found, if it can be attributed to a specific place.
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+/// A place in the puzzle input. Lines and columns are numbered from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+    /// The offending text.
+    pub text: String,
+}
+
+impl PuzzleError {
+    /// Creates an error that is not attributed to a specific place in the input.
+    pub fn new(day: u8, message: impl Display) -> Self {
+        Self {
+            day,
+            location: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "Day {:02}, line {}, column {}: {} (found '{}')",
+                self.day, location.line, location.column, self.message, location.text
+            ),
+            None => write!(f, "Day {:02}: {}", self.day, self.message),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// A day's puzzle input, used to report errors at their location within it.
+#[derive(Clone, Copy)]
+pub struct Input<'a> {
+    pub day: u8,
+    pub text: &'a str,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(day: u8, text: &'a str) -> Self {
+        Self { day, text }
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'a> {
+        self.text.lines()
+    }
+
+    /// Returns the lines of a grid, failing unless it is a non-empty rectangle of tiles found in
+    /// `tiles`.
+    pub fn grid(&self, tiles: &str) -> Result<Vec<&'a str>, PuzzleError> {
+        let rows: Vec<&'a str> = self.lines().collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err(PuzzleError::new(self.day, "grid is empty"));
+        }
+        for row in &rows {
+            if let Some((i, c)) = row.char_indices().find(|&(_, c)| !tiles.contains(c)) {
+                return Err(self.error(&row[i..i + c.len_utf8()], "invalid tile"));
+            }
+            if row.chars().count() != width {
+                return Err(self.error(row, format!("expected a row of {} tiles", width)));
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Creates an error about the given text, which is located within the input if it is a slice
+    /// of it.
+    pub fn error(&self, at: &str, message: impl Display) -> PuzzleError {
+        PuzzleError {
+            day: self.day,
+            location: self.location(at),
+            message: message.to_string(),
+        }
+    }
+
+    /// Parses the given text (a slice of the input), reporting where it is on failure.
+    pub fn parse<T>(&self, text: &str) -> Result<T, PuzzleError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        text.parse::<T>().map_err(|e| {
+            self.error(
+                text,
+                format!("invalid {}: {}", std::any::type_name::<T>(), e),
+            )
+        })
+    }
+
+    /// Returns where the given text is in the input, if it is a slice of it.
+    pub fn location(&self, at: &str) -> Option<Location> {
+        let start = self.text.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize).checked_sub(start)?;
+        if offset + at.len() > self.text.len() {
+            return None;
+        }
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Some(Location {
+            line,
+            column: self.text[line_start..offset].chars().count() + 1,
+            text: at.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Words;
+
+    impl Solver for Words {
+        type Model = Vec<String>;
+
+        fn parse(input: &str) -> Result<Vec<String>, PuzzleError> {
+            if input.is_empty() {
+                return Err(PuzzleError::new(0, "no words"));
+            }
+            Ok(input.split_whitespace().map(str::to_string).collect())
+        }
+
+        fn solve_part_1(words: &Vec<String>) -> Result<Answer, PuzzleError> {
+            Ok(words.len().into())
+        }
+
+        fn solve_part_2(words: &Vec<String>) -> Result<Answer, PuzzleError> {
+            Ok(words.join("-").into())
+        }
+    }
+
+    #[test]
+    fn solver_parses_once_for_both_parts() {
+        let puzzle = Words::create("two words").unwrap();
+        assert_eq!(puzzle.solve_part_1(), Ok(Answer::Int(2)));
+        assert_eq!(puzzle.solve_part_2(), Ok(Answer::from("two-words")));
+        assert_eq!(Words::create("").err().unwrap().message, "no words");
+    }
+
+    #[test]
+    fn answer_uses_smallest_integer_variant() {
+        assert_eq!(Answer::from(42usize), Answer::Int(42));
+        assert_eq!(Answer::from(-7i32), Answer::Int(-7));
+        assert_eq!(Answer::from(u64::MAX), Answer::BigInt(u64::MAX as i128));
+        assert_eq!(Answer::from(i128::MIN), Answer::BigInt(i128::MIN));
+    }
+
+    #[test]
+    fn answer_display_and_json() {
+        let answers = [
+            Answer::Int(42),
+            Answer::BigInt(1 << 70),
+            Answer::from("text"),
+            Answer::NotApplicable,
+        ];
+        let display: Vec<String> = answers.iter().map(Answer::to_string).collect();
+        assert_eq!(display, ["42", "1180591620717411303424", "text", "n/a"]);
+        assert_eq!(
+            serde_json::to_string(&answers).unwrap(),
+            r#"[42,1180591620717411303424,"text",null]"#
+        );
+    }
+
+    #[test]
+    fn error_locates_slice_of_input() {
+        let text = "first line\nsecond line";
+        let input = Input::new(5, text);
+        let error = input.error(&text[18..22], "bad word");
+        assert_eq!(
+            error.location,
+            Some(Location {
+                line: 2,
+                column: 8,
+                text: "line".to_string(),
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "Day 05, line 2, column 8: bad word (found 'line')"
+        );
+    }
+
+    #[test]
+    fn error_without_slice_of_input_has_no_location() {
+        let input = Input::new(5, "some input");
+        let error = input.error("elsewhere", "bad word");
+        assert_eq!(error.location, None);
+        assert_eq!(error.to_string(), "Day 05: bad word");
+    }
+
+    #[test]
+    fn error_locates_empty_line() {
+        let text = "a\n\nb";
+        let input = Input::new(1, text);
+        let line = input.lines().nth(1).unwrap();
+        let location = input.error(line, "empty").location.unwrap();
+        assert_eq!((location.line, location.column), (2, 1));
+    }
+
+    #[test]
+    fn grid_requires_rectangle_of_known_tiles() {
+        assert_eq!(Input::new(3, "#.\n.#\n").grid("#."), Ok(vec!["#.", ".#"]));
+        let error = Input::new(3, "#.\n.").grid("#.").unwrap_err();
+        assert_eq!(error.location.unwrap().line, 2);
+        let error = Input::new(3, "#.\n.x").grid("#.").unwrap_err();
+        assert_eq!(error.location.unwrap().column, 2);
+        assert!(Input::new(3, "").grid("#.").is_err());
+    }
+
+    #[test]
+    fn parse_reports_invalid_numbers() {
+        let text = "12 x4";
+        let input = Input::new(9, text);
+        assert_eq!(input.parse::<i64>(&text[..2]), Ok(12));
+        let error = input.parse::<i64>(&text[3..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Day 09, line 1, column 4: invalid i64: invalid digit found in string (found 'x4')"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::puzzle::{Puzzle, PuzzleError, Solver};
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+
+/// Builds a puzzle for a given day from its raw input.
+pub type PuzzleFactory = fn(&str) -> Result<Box<dyn Puzzle>, PuzzleError>;
+
+/// A day that has a solution available.
+pub struct Entry {
+    pub day: u8,
+    pub create: PuzzleFactory,
+}
+
+impl Entry {
+    const fn new(day: u8, create: PuzzleFactory) -> Self {
+        Self { day, create }
+    }
+}
+
+/// The puzzles of one year's event.
+pub struct Year {
+    pub year: u16,
+    /// The days with a solution, in day order.
+    pub days: &'static [Entry],
+}
+
+impl Year {
+    /// Returns the registry entry for the given day, if it has a solution.
+    pub fn get(&self, day: u8) -> Option<&'static Entry> {
+        self.days.iter().find(|entry| entry.day == day)
+    }
+}
+
+/// Every year with solutions, in year order.
+pub const YEARS: &[Year] = &[Year {
+    year: 2023,
+    days: DAYS_2023,
+}];
+
+/// The days of 2023 with a solution, in day order.
+pub const DAYS_2023: &[Entry] = &[
+    Entry::new(1, day01::Day::create),
+    Entry::new(2, day02::Day::create),
+    Entry::new(3, day03::Day::create),
+    Entry::new(4, day04::Day::create),
+    Entry::new(5, day05::Day::create),
+    Entry::new(6, day06::Day::create),
+    Entry::new(7, day07::Day::create),
+    Entry::new(8, day08::Day::create),
+    Entry::new(9, day09::Day::create),
+    Entry::new(10, day10::Day::create),
+    Entry::new(11, day11::Day::create),
+    Entry::new(12, day12::Day::create),
+    Entry::new(13, day13::Day::create),
+    Entry::new(14, day14::Day::create),
+    Entry::new(15, day15::Day::create),
+    Entry::new(16, day16::Day::create),
+    Entry::new(17, day17::Day::create),
+    Entry::new(18, day18::Day::create),
+    Entry::new(19, day19::Day::create),
+    Entry::new(20, day20::Day::create),
+    Entry::new(21, day21::Day::create),
+    Entry::new(22, day22::Day::create),
+    Entry::new(23, day23::Day::create),
+    Entry::new(24, day24::Day::create),
+    Entry::new(25, day25::Day::create),
+];
+
+/// Returns the registered puzzles of the given year, if it has any.
+pub fn get_year(year: u16) -> Option<&'static Year> {
+    YEARS.iter().find(|registered| registered.year == year)
+}
+
+/// Returns the most recent year with solutions, which is selected when no year is given.
+pub fn latest_year() -> &'static Year {
+    YEARS.last().unwrap()
+}
+
+/// Returns the registry entry for the given day of a year, if it has a solution.
+pub fn get(year: u16, day: u8) -> Option<&'static Entry> {
+    get_year(year)?.get(day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_registered_in_order() {
+        for year in YEARS {
+            let days: Vec<u8> = year.days.iter().map(|entry| entry.day).collect();
+            assert!(days.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(days.iter().all(|day| (1..=25).contains(day)));
+        }
+        let days: Vec<u8> = DAYS_2023.iter().map(|entry| entry.day).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn years_are_registered_in_order() {
+        assert!(YEARS.windows(2).all(|pair| pair[0].year < pair[1].year));
+        assert_eq!(latest_year().year, YEARS.last().unwrap().year);
+    }
+
+    #[test]
+    fn get_finds_registered_days() {
+        for day in 1..=25 {
+            assert_eq!(get(2023, day).unwrap().day, day);
+        }
+        assert!(get(2023, 0).is_none());
+        assert!(get(2023, 26).is_none());
+        assert!(get(2015, 1).is_none());
+        assert!(get_year(2015).is_none());
+    }
+}
@@ -0,0 +1,590 @@
+use crate::answers;
+use crate::answers::{Manifest, Outcome};
+use crate::bench;
+use crate::bench::{Baseline, Benchmark, Verdict};
+use crate::cli::{
+    BenchArgs, CryptArgs, ExamplesArgs, LeaderboardArgs, ProgressArgs, RefetchArgs, RunArgs,
+    Selection, VerifyArgs,
+};
+use crate::progress;
+use crate::standings;
+use crate::timing;
+use crate::timing::DayReport;
+use aoc2023::clock::format_duration;
+use aoc2023::examples;
+use aoc2023::input_fetcher::InputFetcher;
+use aoc2023::registry;
+use aoc2023::secure_store;
+use rayon::prelude::*;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Solves the days selected by the arguments, printing each answer as it is found (or a timing
+/// report once every day is solved). A day whose input cannot be loaded or solved is reported and
+/// skipped. Returns whether every selected day was solved.
+pub fn run(args: &RunArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    if args.parallel {
+        return run_parallel(args, year, fetcher);
+    }
+    let mut reports = Vec::new();
+    let mut solved = true;
+    let loaded = for_each_day(&args.selection, year, fetcher, |entry, input| {
+        let Some(report) = solve(entry, input, args.selection.part) else {
+            solved = false;
+            return;
+        };
+        solved &= report.is_ok();
+        match args.timings {
+            Some(_) => reports.push(report),
+            None => print_answers(&report),
+        }
+    });
+    if let Some(format) = args.timings {
+        println!("{}", timing::render(&reports, format));
+    }
+    loaded && solved
+}
+
+/// Solves the selected days concurrently once all their inputs are loaded, then prints the results
+/// in day order followed by a summary comparing the wall-clock time with the summed per-day time.
+fn run_parallel(args: &RunArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let mut days = Vec::new();
+    let success = for_each_day(&args.selection, year, fetcher, |entry, input| {
+        days.push((entry, input.to_string()));
+    });
+    let start = Instant::now();
+    let results: Vec<_> = days
+        .par_iter()
+        .map(|(entry, input)| timing::solve(entry, input, args.selection.part))
+        .collect();
+    let wall_clock = start.elapsed();
+    let mut solved = true;
+    let reports: Vec<DayReport> = results
+        .into_iter()
+        .filter_map(|result| {
+            let report = result.map_err(|e| eprintln!("Error: {}", e)).ok();
+            solved &= report.as_ref().is_some_and(DayReport::is_ok);
+            report
+        })
+        .collect();
+    match args.timings {
+        Some(format) => println!("{}", timing::render(&reports, format)),
+        None => reports.iter().for_each(print_answers),
+    }
+    if let Some(summary) = timing::summarize_parallel(&reports, wall_clock) {
+        eprintln!("{}", summary);
+    }
+    success && solved
+}
+
+/// Benchmarks the selected days, comparing against and saving baselines as requested. Returns
+/// whether every selected day was benchmarked without a regression against the baseline.
+pub fn bench(args: &BenchArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let baseline = match args.baseline.as_deref().map(Baseline::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Failed to load baseline: {}", e);
+            return false;
+        }
+    };
+    let mut results = Vec::new();
+    let mut solved = true;
+    let mut success = for_each_day(&args.selection, year, fetcher, |entry, input| {
+        let puzzle = match (entry.create)(input) {
+            Ok(puzzle) => puzzle,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                solved = false;
+                return;
+            }
+        };
+        for part in [1, 2] {
+            if args.selection.part.is_some_and(|selected| selected != part) {
+                continue;
+            }
+            let solve = || match part {
+                1 => puzzle.solve_part_1(),
+                _ => puzzle.solve_part_2(),
+            };
+            let stats = match bench::measure(solve, args.warmup, args.iterations) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("Error in part {}: {}", part, e);
+                    solved = false;
+                    continue;
+                }
+            };
+            let benchmark = Benchmark {
+                day: entry.day,
+                part,
+                stats,
+            };
+            let comparison = baseline
+                .as_ref()
+                .and_then(|baseline| baseline.find(entry.day, part))
+                .map(|old| bench::compare(&benchmark.stats, &old.stats, args.threshold));
+            results.push((benchmark, comparison));
+        }
+    });
+    println!("{}", bench::render(&results, args.format));
+    success &= solved;
+    if results
+        .iter()
+        .any(|(_, comparison)| matches!(comparison, Some(c) if c.verdict == Verdict::Regressed))
+    {
+        eprintln!("Performance regressed against the baseline");
+        success = false;
+    }
+    if let Some(path) = &args.save_baseline {
+        let baseline = Baseline {
+            benchmarks: results
+                .into_iter()
+                .map(|(benchmark, _)| benchmark)
+                .collect(),
+        };
+        if let Err(e) = baseline.save(path) {
+            eprintln!("Failed to save baseline: {}", e);
+            success = false;
+        }
+    }
+    success
+}
+
+/// Solves the selected days and checks each answer against the manifest, printing the expected
+/// and actual answers of any mismatch. Returns whether every selected day was solved without a
+/// mismatch.
+pub fn verify(args: &VerifyArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let manifest = match Manifest::load(&args.answers) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", args.answers.display(), e);
+            return false;
+        }
+    };
+    let mut mismatches = 0;
+    let mut solved = true;
+    let loaded = for_each_day(&args.selection, year, fetcher, |entry, input| {
+        let Some(report) = solve(entry, input, args.selection.part) else {
+            solved = false;
+            return;
+        };
+        for (part, result) in [(1, &report.part_1), (2, &report.part_2)] {
+            let Some(result) = result else {
+                continue;
+            };
+            let answer = match &result.answer {
+                Ok(answer) => answer,
+                Err(e) => {
+                    println!("Day {:02} Part {}: ERROR", entry.day, part);
+                    println!("  {}", e);
+                    solved = false;
+                    continue;
+                }
+            };
+            let expected = manifest.lookup(year.year, entry.day, part, input);
+            match answers::check(expected, answer) {
+                Outcome::Correct => {
+                    println!("Day {:02} Part {}: ok ({})", entry.day, part, answer)
+                }
+                Outcome::Incorrect { expected } => {
+                    println!("Day {:02} Part {}: MISMATCH", entry.day, part);
+                    println!("  - {}", expected);
+                    println!("  + {}", answer);
+                    mismatches += 1;
+                }
+                Outcome::Unknown => println!(
+                    "Day {:02} Part {}: no known answer ({})",
+                    entry.day, part, answer
+                ),
+                Outcome::NotApplicable => {
+                    println!("Day {:02} Part {}: not applicable", entry.day, part)
+                }
+            }
+        }
+    });
+    if mismatches > 0 {
+        eprintln!("{} answer(s) did not match the manifest", mismatches);
+    }
+    loaded && solved && mismatches == 0
+}
+
+/// Extracts the examples from the puzzle description of each selected day into fixtures, printing
+/// the path of each one written. Returns whether examples were found for every selected day.
+pub fn examples(args: &ExamplesArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let selection = Selection {
+        days: args.days.clone(),
+        ..Default::default()
+    };
+    let entries = match select_days(&selection, year) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let mut success = true;
+    for entry in entries {
+        announce_unlock_wait(entry.day, fetcher);
+        let page = match fetcher.get_page(entry.day) {
+            Ok(page) => page,
+            Err(e) => {
+                eprintln!("Day {:02}: failed to load puzzle page: {}", entry.day, e);
+                success = false;
+                continue;
+            }
+        };
+        let found = examples::extract(&page);
+        if found.is_empty() {
+            eprintln!("Day {:02}: no examples found", entry.day);
+            success = false;
+            continue;
+        }
+        match examples::write_fixtures(&args.dir, year.year, entry.day, &found) {
+            Ok(written) => written
+                .iter()
+                .for_each(|path| println!("Wrote {}", path.display())),
+            Err(e) => {
+                eprintln!("Day {:02}: failed to write fixtures: {}", entry.day, e);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Checks the session token against the Advent of Code website. Returns whether it was accepted.
+pub fn check_token(fetcher: &InputFetcher) -> bool {
+    match fetcher.check_session_token() {
+        Ok(Some(user)) => println!("Session token is valid (logged in as {})", user),
+        Ok(None) => println!("Session token is valid"),
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    }
+    true
+}
+
+/// Prints the standings of a private leaderboard. Returns whether it could be fetched.
+pub fn leaderboard(args: &LeaderboardArgs, fetcher: &InputFetcher) -> bool {
+    match fetcher.get_leaderboard(args.id) {
+        Ok((leaderboard, stale)) => {
+            if let Some(e) = stale {
+                eprintln!("Using the cached leaderboard: {}", e);
+            }
+            println!(
+                "{}",
+                standings::render(&leaderboard, fetcher.year(), args.format)
+            );
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        }
+    }
+}
+
+/// Prints how the stars on the website's calendar compare with the days that have solutions and
+/// recorded answers, optionally updating the README's badges to match. Returns whether the
+/// calendar could be fetched and the README updated.
+pub fn progress(args: &ProgressArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let manifest = match Manifest::load(&args.answers) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", args.answers.display(), e);
+            return false;
+        }
+    };
+    let stars = match fetcher.get_stars() {
+        Ok(stars) => stars,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let days = progress::compare(year, &stars, &manifest);
+    println!("{}", progress::render(&days));
+    let Some(path) = &args.update_readme else {
+        return true;
+    };
+    let updated = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|readme| progress::update_badges(&readme, &days))
+        .and_then(|readme| fs::write(path, readme).map_err(|e| e.to_string()));
+    match updated {
+        Ok(()) => {
+            println!("Updated the badges in {}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to update {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Fetches the inputs of the selected days again, replacing the cached copies. Without any selected
+/// days, only the cached inputs that are corrupt are replaced. Returns whether every input that
+/// needed fetching was fetched.
+pub fn refetch(args: &RefetchArgs, year: &registry::Year, fetcher: &InputFetcher) -> bool {
+    let mut days = args.days.clone();
+    days.sort_unstable();
+    days.dedup();
+    if days.is_empty() {
+        days = year
+            .days
+            .iter()
+            .map(|entry| entry.day)
+            .filter(|&day| {
+                fetcher
+                    .verify_cached_input(day)
+                    .map_err(|e| eprintln!("{}", e))
+                    .is_err()
+            })
+            .collect();
+        if days.is_empty() {
+            println!("Every cached input is intact");
+        }
+    }
+    let mut success = true;
+    for day in days {
+        announce_unlock_wait(day, fetcher);
+        match fetcher.refetch_input(day) {
+            Ok(input) => println!("Day {:02}: fetched {} bytes", day, input.len()),
+            Err(e) => {
+                eprintln!("Day {:02}: failed to refetch input: {}", day, e);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Encrypts the files in place with the fetcher's store, skipping those already encrypted. Returns
+/// whether every file was encrypted.
+pub fn encrypt(args: &CryptArgs, fetcher: &InputFetcher) -> bool {
+    let Some(store) = fetcher.store() else {
+        eprintln!("No key to encrypt with; pass --key-file or set AOC_STORE_KEY_FILE or AOC_STORE_PASSPHRASE");
+        return false;
+    };
+    for_each_file(&args.paths, |path| {
+        let data = fs::read(path)?;
+        if secure_store::is_encrypted(&data) {
+            return Ok("already encrypted");
+        }
+        fs::write(path, store.encrypt(&data)?)?;
+        Ok("encrypted")
+    })
+}
+
+/// Decrypts the files in place with the fetcher's store, skipping those that are not encrypted.
+/// Returns whether every file was decrypted.
+pub fn decrypt(args: &CryptArgs, fetcher: &InputFetcher) -> bool {
+    let Some(store) = fetcher.store() else {
+        eprintln!("No key to decrypt with; pass --key-file or set AOC_STORE_KEY_FILE or AOC_STORE_PASSPHRASE");
+        return false;
+    };
+    for_each_file(&args.paths, |path| {
+        let data = fs::read(path)?;
+        if !secure_store::is_encrypted(&data) {
+            return Ok("not encrypted");
+        }
+        fs::write(path, store.decrypt(&data)?)?;
+        Ok("decrypted")
+    })
+}
+
+/// Applies the action to each file, printing what was done to it or why it failed. Returns
+/// whether the action succeeded for every file.
+fn for_each_file(
+    paths: &[PathBuf],
+    action: impl Fn(&Path) -> Result<&'static str, Box<dyn Error>>,
+) -> bool {
+    let mut success = true;
+    for path in paths {
+        match action(path) {
+            Ok(done) => println!("{}: {}", path.display(), done),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Loads the input of each selected day and passes it to the callback, reporting any day whose
+/// input cannot be loaded. Returns whether every selected day's input was loaded.
+fn for_each_day(
+    selection: &Selection,
+    year: &registry::Year,
+    fetcher: &InputFetcher,
+    mut callback: impl FnMut(&'static registry::Entry, &str),
+) -> bool {
+    let entries = match select_days(selection, year) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let mut success = true;
+    for entry in entries {
+        match load_input(entry.day, selection.input.as_deref(), fetcher) {
+            Ok(input) => callback(entry, &input),
+            Err(e) => {
+                eprintln!("Day {:02}: failed to load input: {}", entry.day, e);
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Solves a day, reporting the error if its input cannot be parsed.
+fn solve(entry: &registry::Entry, input: &str, part: Option<u8>) -> Option<DayReport> {
+    timing::solve(entry, input, part)
+        .map_err(|e| eprintln!("Error: {}", e))
+        .ok()
+}
+
+fn print_answers(report: &DayReport) {
+    for (number, part) in [(1, &report.part_1), (2, &report.part_2)] {
+        match part.as_ref().map(|part| &part.answer) {
+            Some(Ok(answer)) => println!("Day {:02} Part {}: {}", report.day, number, answer),
+            Some(Err(e)) => eprintln!("Error in part {}: {}", number, e),
+            None => {}
+        }
+    }
+}
+
+/// Returns the registry entries for the selected days of the year in day order (every day if none
+/// selected).
+fn select_days(
+    selection: &Selection,
+    year: &registry::Year,
+) -> Result<Vec<&'static registry::Entry>, String> {
+    let mut days = selection.days.clone();
+    days.sort_unstable();
+    days.dedup();
+    if selection.input.is_some() && days.len() != 1 {
+        return Err("--input requires exactly one --day".to_string());
+    }
+    if days.is_empty() {
+        return Ok(year.days.iter().collect());
+    }
+    days.iter()
+        .map(|&day| {
+            year.get(day)
+                .ok_or(format!("Day {:02} of {} has no solution", day, year.year))
+        })
+        .collect()
+}
+
+/// Returns the input for the given day, read from the path if one is given ('-' meaning stdin),
+/// and otherwise from the input fetcher.
+fn load_input(
+    day: u8,
+    path: Option<&Path>,
+    fetcher: &InputFetcher,
+) -> Result<String, Box<dyn Error>> {
+    match path {
+        Some(path) if path == Path::new("-") => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => fetcher.read_file(path),
+        None => {
+            announce_unlock_wait(day, fetcher);
+            fetcher.get_input(day)
+        }
+    }
+}
+
+/// Prints how long the fetcher is about to wait for the day to unlock, if it waits for locked days
+/// and the day is still locked.
+fn announce_unlock_wait(day: u8, fetcher: &InputFetcher) {
+    if !fetcher.waits_for_unlock() {
+        return;
+    }
+    if let Ok(Some(remaining)) = fetcher.time_until_unlock(day) {
+        eprintln!(
+            "Waiting {} for day {} to unlock",
+            format_duration(remaining),
+            day
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::{NamedTempFile, TempDir};
+
+    #[test]
+    fn select_days_defaults_to_every_day() {
+        let entries = select_days(&Selection::default(), registry::latest_year()).unwrap();
+        assert_eq!(entries.len(), 25);
+    }
+
+    #[test]
+    fn select_days_sorts_and_deduplicates() {
+        let selection = Selection {
+            days: vec![17, 3, 17],
+            ..Default::default()
+        };
+        let days: Vec<u8> = select_days(&selection, registry::latest_year())
+            .unwrap()
+            .iter()
+            .map(|entry| entry.day)
+            .collect();
+        assert_eq!(days, vec![3, 17]);
+    }
+
+    #[test]
+    fn select_days_requires_single_day_with_input() {
+        let selection = Selection {
+            input: Some(PathBuf::from("-")),
+            ..Default::default()
+        };
+        assert!(select_days(&selection, registry::latest_year()).is_err());
+        let selection = Selection {
+            days: vec![1, 2],
+            input: Some(PathBuf::from("-")),
+            ..Default::default()
+        };
+        assert!(select_days(&selection, registry::latest_year()).is_err());
+    }
+
+    #[test]
+    fn load_input_reads_from_file() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(input_file.path(), "1abc2").unwrap();
+        let input_dir = TempDir::new().unwrap();
+        let fetcher = unreachable_fetcher(&input_dir);
+        let input = load_input(1, Some(input_file.path()), &fetcher).unwrap();
+        assert_eq!(input, "1abc2");
+    }
+
+    #[test]
+    fn load_input_fails_for_missing_file() {
+        let input_dir = TempDir::new().unwrap();
+        let fetcher = unreachable_fetcher(&input_dir);
+        let missing = input_dir.path().join("missing");
+        assert!(load_input(1, Some(&missing), &fetcher).is_err());
+    }
+
+    fn unreachable_fetcher(input_dir: &TempDir) -> InputFetcher {
+        InputFetcher::create_custom(
+            "http://localhost:0",
+            input_dir.path(),
+            &input_dir.path().join("missing_cookie.txt"),
+        )
+    }
+}
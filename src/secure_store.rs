@@ -0,0 +1,324 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// The environment variable holding the passphrase that files are encrypted with.
+pub const PASSPHRASE_ENV_VAR: &str = "AOC_STORE_PASSPHRASE";
+
+/// The environment variable holding the path of a key file that files are encrypted with. It takes
+/// precedence over the passphrase.
+pub const KEY_FILE_ENV_VAR: &str = "AOC_STORE_KEY_FILE";
+
+/// The start of every encrypted file, which tells it apart from a plain one.
+const MAGIC: &[u8; 8] = b"AOC-ENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+/// The length of an encrypted file's header: the magic, the three Argon2 parameters, the salt and
+/// the nonce.
+const HEADER_LEN: usize = MAGIC.len() + 12 + SALT_LEN + NONCE_LEN;
+
+/// Encrypts and decrypts files (such as puzzle inputs and the session token) so that they can be
+/// kept at rest, or even published, without revealing them.
+///
+/// The key is derived from a passphrase or the contents of a key file with Argon2id, using a salt
+/// and cost parameters that are stored in each encrypted file's header along with its nonce, so
+/// that a file can be decrypted with the secret alone. The contents are encrypted with
+/// XChaCha20-Poly1305, which also detects tampering and wrong keys.
+pub struct SecureStore {
+    secret: Vec<u8>,
+    params: KdfParams,
+    /// The salt of the files encrypted by this store.
+    salt: [u8; SALT_LEN],
+    /// The keys derived so far, by salt and parameters, since deriving one is deliberately slow.
+    keys: Mutex<HashMap<([u8; SALT_LEN], KdfParams), Key>>,
+}
+
+/// The cost parameters of Argon2id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct KdfParams {
+    /// The memory used, in KiB.
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+/// The most costly parameters accepted from a file's header, well above the defaults, so that a
+/// crafted file cannot make decrypting it use gigabytes of memory or take hours.
+const MAX_KDF_PARAMS: KdfParams = KdfParams {
+    m_cost: 256 * 1024,
+    t_cost: 16,
+    p_cost: 16,
+};
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl SecureStore {
+    /// Creates a store whose key is derived from the passphrase.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self::from_secret(passphrase.as_bytes().to_vec(), KdfParams::default())
+    }
+
+    /// Creates a store whose key is derived from the contents of the key file.
+    pub fn from_key_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let secret = fs::read(path)
+            .map_err(|e| format!("Failed to read key file {}: {}", path.display(), e))?;
+        if secret.is_empty() {
+            return Err(format!("Key file {} is empty", path.display()).into());
+        }
+        Ok(Self::from_secret(secret, KdfParams::default()))
+    }
+
+    /// Creates a store from the key file or passphrase in the environment, if either is set.
+    pub fn from_env() -> Result<Option<Self>, Box<dyn Error>> {
+        if let Some(path) = env::var_os(KEY_FILE_ENV_VAR).filter(|path| !path.is_empty()) {
+            return Self::from_key_file(Path::new(&path)).map(Some);
+        }
+        Ok(env::var(PASSPHRASE_ENV_VAR)
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+            .map(|passphrase| Self::from_passphrase(&passphrase)))
+    }
+
+    fn from_secret(secret: Vec<u8>, params: KdfParams) -> Self {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            secret,
+            params,
+            salt,
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a store with the cheapest parameters Argon2 allows, since tests are built without
+    /// optimisations.
+    #[cfg(test)]
+    pub(crate) fn for_tests(secret: &str) -> Self {
+        let params = KdfParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        Self::from_secret(secret.as_bytes().to_vec(), params)
+    }
+
+    /// Encrypts the contents, returning them with the header needed to decrypt them.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let cipher = XChaCha20Poly1305::new(&self.key(self.salt, self.params)?);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Failed to encrypt")?;
+        let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        data.extend_from_slice(MAGIC);
+        for value in [self.params.m_cost, self.params.t_cost, self.params.p_cost] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    /// Decrypts contents encrypted by a store with the same secret.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        if !is_encrypted(data) || data.len() < HEADER_LEN {
+            return Err("Not an encrypted file".into());
+        }
+        let (header, ciphertext) = data.split_at(HEADER_LEN);
+        let number = |i: usize| {
+            let start = MAGIC.len() + 4 * i;
+            u32::from_le_bytes(header[start..start + 4].try_into().unwrap())
+        };
+        let params = KdfParams {
+            m_cost: number(0),
+            t_cost: number(1),
+            p_cost: number(2),
+        };
+        if params.m_cost > MAX_KDF_PARAMS.m_cost
+            || params.t_cost > MAX_KDF_PARAMS.t_cost
+            || params.p_cost > MAX_KDF_PARAMS.p_cost
+        {
+            return Err(format!(
+                "Key derivation parameters {:?} exceed the maximum of {:?}",
+                params, MAX_KDF_PARAMS
+            )
+            .into());
+        }
+        let salt_start = MAGIC.len() + 12;
+        let salt = header[salt_start..salt_start + SALT_LEN]
+            .try_into()
+            .unwrap();
+        let nonce = XNonce::from_slice(&header[salt_start + SALT_LEN..]);
+        let cipher = XChaCha20Poly1305::new(&self.key(salt, params)?);
+        cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| "Failed to decrypt (wrong key or corrupted file)".into())
+    }
+
+    /// Returns the key for the salt and parameters, deriving it if it has not been yet.
+    fn key(&self, salt: [u8; SALT_LEN], params: KdfParams) -> Result<Key, Box<dyn Error>> {
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(key) = keys.get(&(salt, params)) {
+            return Ok(*key);
+        }
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+                .map_err(|e| format!("Invalid key derivation parameters: {}", e))?,
+        );
+        let mut key = Key::default();
+        argon2
+            .hash_password_into(&self.secret, &salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+        keys.insert((salt, params), key);
+        Ok(key)
+    }
+
+    /// Reads a file as text, decrypting it if it is encrypted. Returns whether it was.
+    pub fn read(store: Option<&Self>, path: &Path) -> Result<(String, bool), Box<dyn Error>> {
+        let data = fs::read(path)?;
+        if !is_encrypted(&data) {
+            return Ok((String::from_utf8(data)?, false));
+        }
+        let store = store.ok_or_else(|| {
+            format!(
+                "{} is encrypted; set {} or {} to decrypt it",
+                path.display(),
+                PASSPHRASE_ENV_VAR,
+                KEY_FILE_ENV_VAR
+            )
+        })?;
+        let plaintext = store
+            .decrypt(&data)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok((String::from_utf8(plaintext)?, true))
+    }
+
+    /// Writes the text to a file, encrypting it if there is a store.
+    pub fn write(store: Option<&Self>, path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+        match store {
+            Some(store) => fs::write(path, store.encrypt(contents.as_bytes())?)?,
+            None => fs::write(path, contents)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the contents were encrypted by a store.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Reads a puzzle input file, decrypting it with the key file or passphrase in the environment if
+/// it is encrypted. The key is only derived once.
+pub fn read_input_file(path: impl AsRef<Path>) -> Result<String, Box<dyn Error>> {
+    static STORE: OnceLock<Result<Option<SecureStore>, String>> = OnceLock::new();
+    let store = STORE.get_or_init(|| SecureStore::from_env().map_err(|e| e.to_string()));
+    let store = store.as_ref().map_err(|e| e.clone())?;
+    Ok(SecureStore::read(store.as_ref(), path.as_ref())?.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(secret: &str) -> SecureStore {
+        SecureStore::for_tests(secret)
+    }
+
+    #[test]
+    fn encrypted_contents_round_trip() {
+        let store = store("correct horse battery staple");
+        let data = store.encrypt(b"1abc2\npqr3stu8vwx\n").unwrap();
+        assert!(is_encrypted(&data));
+        assert!(!data.windows(5).any(|window| window == b"1abc2"));
+        assert_eq!(store.decrypt(&data).unwrap(), b"1abc2\npqr3stu8vwx\n");
+        // Every encryption uses a new nonce.
+        assert_ne!(
+            store.encrypt(b"1abc2").unwrap(),
+            store.encrypt(b"1abc2").unwrap()
+        );
+    }
+
+    #[test]
+    fn files_decrypt_with_same_secret_only() {
+        let data = store("secret").encrypt(b"input").unwrap();
+        // The salt and parameters are read from the header, not taken from the decrypting store.
+        assert_eq!(store("secret").decrypt(&data).unwrap(), b"input");
+        assert!(store("other").decrypt(&data).is_err());
+
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(store("secret").decrypt(&tampered).is_err());
+        assert!(store("secret").decrypt(b"input").is_err());
+        assert!(store("secret").decrypt(&data[..HEADER_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn costly_header_parameters_are_rejected() {
+        let data = store("secret").encrypt(b"input").unwrap();
+        // Each parameter in turn: 4 TiB of memory, and billions of passes or lanes.
+        for i in 0..3 {
+            let mut crafted = data.clone();
+            let start = MAGIC.len() + 4 * i;
+            crafted[start..start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            let error = store("secret").decrypt(&crafted).unwrap_err().to_string();
+            assert!(error.contains("exceed the maximum"), "{}", error);
+        }
+        // Files encrypted with the defaults still decrypt.
+        let defaults = KdfParams::default();
+        assert!(defaults.m_cost <= MAX_KDF_PARAMS.m_cost);
+        assert!(defaults.t_cost <= MAX_KDF_PARAMS.t_cost);
+        assert!(defaults.p_cost <= MAX_KDF_PARAMS.p_cost);
+    }
+
+    #[test]
+    fn read_decrypts_only_encrypted_files() {
+        let dir = TempDir::new().unwrap();
+        let store = store("secret");
+        let encrypted = dir.path().join("01");
+        let plain = dir.path().join("02");
+        SecureStore::write(Some(&store), &encrypted, "1abc2").unwrap();
+        SecureStore::write(None, &plain, "Game 1").unwrap();
+        assert!(is_encrypted(&fs::read(&encrypted).unwrap()));
+        assert_eq!(
+            SecureStore::read(Some(&store), &encrypted).unwrap(),
+            ("1abc2".to_string(), true)
+        );
+        assert_eq!(
+            SecureStore::read(Some(&store), &plain).unwrap(),
+            ("Game 1".to_string(), false)
+        );
+        let error = SecureStore::read(None, &encrypted).unwrap_err().to_string();
+        assert!(error.contains(PASSPHRASE_ENV_VAR), "{}", error);
+    }
+
+    #[test]
+    fn key_file_must_not_be_empty() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        assert!(SecureStore::from_key_file(&path).is_err());
+        fs::write(&path, "").unwrap();
+        assert!(SecureStore::from_key_file(&path).is_err());
+        fs::write(&path, [7; 32]).unwrap();
+        assert!(SecureStore::from_key_file(&path).is_ok());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::cli::ReportFormat;
+use crate::table;
+use aoc2023::clock::{format_duration, unlock_time};
+use aoc2023::leaderboard::{Leaderboard, Member};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Renders the standings of a private leaderboard of the given year, with when each member got
+/// each star and how long they took between the two stars of a day.
+pub fn render(leaderboard: &Leaderboard, year: u16, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Table => render_table(leaderboard, year),
+        ReportFormat::Json => render_json(leaderboard),
+        ReportFormat::Csv => render_csv(leaderboard),
+    }
+}
+
+/// Renders the standings, followed by one row per member and day with the time each star was got
+/// after the day unlocked.
+fn render_table(leaderboard: &Leaderboard, year: u16) -> String {
+    let standings = leaderboard.standings();
+    let days = leaderboard.days();
+    let last_day = days.last().copied().unwrap_or(0);
+    let rows: Vec<Vec<String>> = standings
+        .iter()
+        .enumerate()
+        .map(|(i, member)| {
+            vec![
+                format!("{}. {}", i + 1, member.display_name()),
+                member.local_score.to_string(),
+                member.stars.to_string(),
+                (1..=last_day).map(|day| day_marker(member, day)).collect(),
+            ]
+        })
+        .collect();
+    let mut report = table::render(&["Member", "Score", "Stars", "Days"], &rows);
+
+    let since_unlock = |time: Option<DateTime<Utc>>, day: u8| {
+        time.zip(unlock_time(year, day))
+            .map(|(time, unlock_time)| format_duration(time - unlock_time))
+            .unwrap_or_default()
+    };
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .flat_map(|&day| standings.iter().map(move |member| (day, member)))
+        .filter(|(day, member)| member.star_time(*day, 1).is_some())
+        .map(|(day, member)| {
+            vec![
+                member.display_name(),
+                format!("{:02}", day),
+                since_unlock(member.star_time(day, 1), day),
+                since_unlock(member.star_time(day, 2), day),
+                member.delta(day).map(format_duration).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    if !rows.is_empty() {
+        report.push_str("\n\n");
+        report.push_str(&table::render(
+            &["Member", "Day", "Part 1", "Part 2", "Delta"],
+            &rows,
+        ));
+    }
+    report
+}
+
+/// Returns how a day is shown in a member's standing: `*` for both stars, `+` for only the first
+/// and `.` for none.
+fn day_marker(member: &Member, day: u8) -> char {
+    match (member.star_time(day, 1), member.star_time(day, 2)) {
+        (Some(_), Some(_)) => '*',
+        (Some(_), None) => '+',
+        _ => '.',
+    }
+}
+
+fn render_json(leaderboard: &Leaderboard) -> String {
+    #[derive(Serialize)]
+    struct Standing {
+        rank: usize,
+        id: u64,
+        name: String,
+        local_score: u32,
+        stars: u32,
+        days: Vec<DayStars>,
+    }
+    #[derive(Serialize)]
+    struct DayStars {
+        day: u8,
+        part1: Option<DateTime<Utc>>,
+        part2: Option<DateTime<Utc>>,
+        delta_seconds: Option<i64>,
+    }
+    #[derive(Serialize)]
+    struct Report {
+        event: String,
+        owner_id: u64,
+        standings: Vec<Standing>,
+    }
+    let standings = leaderboard
+        .standings()
+        .iter()
+        .enumerate()
+        .map(|(i, member)| Standing {
+            rank: i + 1,
+            id: member.id,
+            name: member.display_name(),
+            local_score: member.local_score,
+            stars: member.stars,
+            days: member
+                .completion_day_level
+                .keys()
+                .map(|&day| DayStars {
+                    day,
+                    part1: member.star_time(day, 1),
+                    part2: member.star_time(day, 2),
+                    delta_seconds: member.delta(day).map(|delta| delta.num_seconds()),
+                })
+                .collect(),
+        })
+        .collect();
+    let report = Report {
+        event: leaderboard.event.clone(),
+        owner_id: leaderboard.owner_id,
+        standings,
+    };
+    serde_json::to_string_pretty(&report).expect("Failed to serialize the leaderboard")
+}
+
+/// Renders one row per member and day on which they got a star, with the times as RFC 3339.
+fn render_csv(leaderboard: &Leaderboard) -> String {
+    let format_time =
+        |time: Option<DateTime<Utc>>| time.map(|time| time.to_rfc3339()).unwrap_or_default();
+    let rows: Vec<Vec<String>> = leaderboard
+        .standings()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, member)| {
+            member.completion_day_level.keys().map(move |&day| {
+                vec![
+                    (i + 1).to_string(),
+                    member.display_name(),
+                    member.local_score.to_string(),
+                    format!("{:02}", day),
+                    format_time(member.star_time(day, 1)),
+                    format_time(member.star_time(day, 2)),
+                    member
+                        .delta(day)
+                        .map(|delta| delta.num_seconds().to_string())
+                        .unwrap_or_default(),
+                ]
+            })
+        })
+        .collect();
+    table::render_csv(
+        &[
+            "Rank",
+            "Member",
+            "Score",
+            "Day",
+            "Part 1",
+            "Part 2",
+            "Delta (s)",
+        ],
+        &rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Leaderboard {
+        Leaderboard::parse(include_str!("../resources/leaderboard/private.json")).unwrap()
+    }
+
+    #[test]
+    fn render_table_shows_standings_and_star_times() {
+        let report = render(&fixture(), 2023, ReportFormat::Table);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Member                     Score  Stars  Days");
+        assert_eq!(lines[2], "1. Olivia                     14      4    **");
+        assert_eq!(lines[4], "3. (anonymous user #3003)      2      1    +.");
+        assert_eq!(lines[5], "4. Idle                        0      0    ..");
+        assert_eq!(
+            lines[10],
+            "Sam                      01      2m 0s  1h 0m 0s  58m 0s"
+        );
+        assert_eq!(lines[11], "(anonymous user #3003)   01  12h 0m 0s");
+        assert_eq!(lines[13], "Sam                      02      5m 0s");
+    }
+
+    #[test]
+    fn render_json_reports_timestamps_and_deltas() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&fixture(), 2023, ReportFormat::Json)).unwrap();
+        let sam = &json["standings"][1];
+        assert_eq!(sam["rank"], 2);
+        assert_eq!(sam["name"], "Sam");
+        assert_eq!(sam["days"][0]["part1"], "2023-12-01T05:02:00Z");
+        assert_eq!(sam["days"][0]["delta_seconds"], 3480);
+        assert!(sam["days"][1]["part2"].is_null());
+        assert_eq!(json["standings"][3]["days"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn render_csv_has_one_row_per_member_and_day() {
+        let report = render(&fixture(), 2023, ReportFormat::Csv);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 1 + 5);
+        assert_eq!(
+            lines[1],
+            "1,Olivia,14,01,2023-12-01T05:05:00+00:00,2023-12-01T05:20:00+00:00,900"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+/// Renders rows as an aligned plain-text table. The first column is left-aligned and every other
+/// column is right-aligned.
+pub fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    let mut lines = vec![render_row(&headers, &widths)];
+    lines.push(
+        widths
+            .iter()
+            .map(|&width| "-".repeat(width))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    lines.extend(rows.iter().map(|row| render_row(row, &widths)));
+    lines.join("\n")
+}
+
+fn render_row(cells: &[String], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(i, (cell, &width))| {
+            if i == 0 {
+                format!("{:<width$}", cell)
+            } else {
+                format!("{:>width$}", cell)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
+/// Renders rows as CSV, quoting fields where needed.
+pub fn render_csv(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut lines = vec![headers
+        .iter()
+        .map(|header| csv_field(header))
+        .collect::<Vec<_>>()
+        .join(",")];
+    lines.extend(rows.iter().map(|row| {
+        row.iter()
+            .map(|cell| csv_field(cell))
+            .collect::<Vec<_>>()
+            .join(",")
+    }));
+    lines.join("\n")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_aligns_columns() {
+        let rows = vec![
+            vec!["01".to_string(), "5".to_string()],
+            vec!["Total".to_string(), "1234".to_string()],
+        ];
+        let table = render(&["Day", "Time"], &rows);
+        assert_eq!(
+            table,
+            "Day    Time\n\
+             -----  ----\n\
+             01        5\n\
+             Total  1234"
+        );
+    }
+
+    #[test]
+    fn render_csv_quotes_special_fields() {
+        let rows = vec![vec!["a,b".to_string(), "say \"hi\"".to_string()]];
+        let csv = render_csv(&["x", "y"], &rows);
+        assert_eq!(csv, "x,y\n\"a,b\",\"say \"\"hi\"\"\"");
+    }
+}
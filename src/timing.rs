@@ -0,0 +1,330 @@
+use crate::cli::ReportFormat;
+use crate::table;
+use aoc2023::puzzle::{Answer, PuzzleError};
+use aoc2023::registry::Entry;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+/// The answers for a single day along with how long each stage took to compute.
+#[derive(Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    #[serde(rename = "parse_ns", serialize_with = "serialize_nanos")]
+    pub parse: Duration,
+    pub part_1: Option<PartReport>,
+    pub part_2: Option<PartReport>,
+}
+
+/// The answer (or the error raised instead) for one part, serialized with either an `answer` or an
+/// `error` field.
+pub struct PartReport {
+    pub answer: Result<Answer, PuzzleError>,
+    pub duration: Duration,
+}
+
+impl Serialize for PartReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PartReport", 2)?;
+        match &self.answer {
+            Ok(answer) => state.serialize_field("answer", answer)?,
+            Err(e) => state.serialize_field("error", &e.to_string())?,
+        }
+        state.serialize_field("time_ns", &(self.duration.as_nanos() as u64))?;
+        state.end()
+    }
+}
+
+impl DayReport {
+    /// Returns the time taken to parse the input and solve the requested parts.
+    pub fn total(&self) -> Duration {
+        self.parse
+            + [&self.part_1, &self.part_2]
+                .into_iter()
+                .flatten()
+                .map(|part| part.duration)
+                .sum::<Duration>()
+    }
+
+    /// Returns whether every requested part was solved without an error.
+    pub fn is_ok(&self) -> bool {
+        [&self.part_1, &self.part_2]
+            .into_iter()
+            .flatten()
+            .all(|part| part.answer.is_ok())
+    }
+}
+
+/// Parses the input and solves the requested part (both parts if none), timing each stage. Fails
+/// if the input cannot be parsed; a part that fails is reported with its error.
+pub fn solve(entry: &Entry, input: &str, part: Option<u8>) -> Result<DayReport, PuzzleError> {
+    let start = Instant::now();
+    let puzzle = (entry.create)(input)?;
+    let parse = start.elapsed();
+    let part_1 = (part != Some(2)).then(|| timed(|| puzzle.solve_part_1()));
+    let part_2 = (part != Some(1)).then(|| timed(|| puzzle.solve_part_2()));
+    Ok(DayReport {
+        day: entry.day,
+        parse,
+        part_1,
+        part_2,
+    })
+}
+
+fn timed(solve: impl FnOnce() -> Result<Answer, PuzzleError>) -> PartReport {
+    let start = Instant::now();
+    let answer = solve();
+    PartReport {
+        answer,
+        duration: start.elapsed(),
+    }
+}
+
+/// Summarizes a parallel run, comparing the wall-clock time with the sum of every day's own
+/// wall-clock time and naming the slowest day. Returns nothing if no day was solved. The sum is
+/// not CPU time: days that compete for cores each count the time spent waiting for one, so it
+/// overstates the work done in parallel.
+pub fn summarize_parallel(reports: &[DayReport], wall_clock: Duration) -> Option<String> {
+    let slowest = reports.iter().max_by_key(|report| report.total())?;
+    let summed = reports.iter().map(DayReport::total).sum::<Duration>();
+    let share = |duration: Duration| {
+        if wall_clock.is_zero() {
+            0.0
+        } else {
+            100.0 * duration.as_secs_f64() / wall_clock.as_secs_f64()
+        }
+    };
+    Some(format!(
+        "Solved {} day(s) in {} wall-clock; summed per-day wall-clock {} ({:.0}% of wall-clock); \
+         slowest: Day {:02} at {} ({:.0}% of wall-clock)",
+        reports.len(),
+        format_duration(wall_clock),
+        format_duration(summed),
+        share(summed),
+        slowest.day,
+        format_duration(slowest.total()),
+        share(slowest.total()),
+    ))
+}
+
+/// Renders the reports in the given format.
+pub fn render(reports: &[DayReport], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Table => render_table(reports),
+        ReportFormat::Json => render_json(reports),
+        ReportFormat::Csv => render_csv(reports),
+    }
+}
+
+fn render_table(reports: &[DayReport]) -> String {
+    let headers = [
+        "Day",
+        "Parse",
+        "Part 1",
+        "Part 2",
+        "Total",
+        "Part 1 Answer",
+        "Part 2 Answer",
+    ];
+    let mut rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|report| {
+            vec![
+                format!("{:02}", report.day),
+                format_duration(report.parse),
+                format_part(&report.part_1, |part| format_duration(part.duration)),
+                format_part(&report.part_2, |part| format_duration(part.duration)),
+                format_duration(report.total()),
+                format_part(&report.part_1, format_answer),
+                format_part(&report.part_2, format_answer),
+            ]
+        })
+        .collect();
+    let total = |stage: fn(&DayReport) -> Duration| reports.iter().map(stage).sum::<Duration>();
+    rows.push(vec![
+        "Total".to_string(),
+        format_duration(total(|report| report.parse)),
+        format_duration(total(|report| part_duration(&report.part_1))),
+        format_duration(total(|report| part_duration(&report.part_2))),
+        format_duration(total(DayReport::total)),
+        String::new(),
+        String::new(),
+    ]);
+    table::render(&headers, &rows)
+}
+
+fn render_json(reports: &[DayReport]) -> String {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        days: &'a [DayReport],
+        #[serde(serialize_with = "serialize_nanos")]
+        total_ns: Duration,
+    }
+    let report = Report {
+        days: reports,
+        total_ns: reports.iter().map(DayReport::total).sum(),
+    };
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+fn render_csv(reports: &[DayReport]) -> String {
+    let headers = [
+        "day",
+        "parse_ns",
+        "part_1_ns",
+        "part_2_ns",
+        "total_ns",
+        "part_1_answer",
+        "part_2_answer",
+    ];
+    let rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|report| {
+            vec![
+                report.day.to_string(),
+                report.parse.as_nanos().to_string(),
+                format_part(&report.part_1, |part| part.duration.as_nanos().to_string()),
+                format_part(&report.part_2, |part| part.duration.as_nanos().to_string()),
+                report.total().as_nanos().to_string(),
+                format_part(&report.part_1, format_answer),
+                format_part(&report.part_2, format_answer),
+            ]
+        })
+        .collect();
+    table::render_csv(&headers, &rows)
+}
+
+fn part_duration(part: &Option<PartReport>) -> Duration {
+    part.as_ref().map_or(Duration::ZERO, |part| part.duration)
+}
+
+fn format_part(part: &Option<PartReport>, format: impl Fn(&PartReport) -> String) -> String {
+    part.as_ref().map_or(String::new(), format)
+}
+
+fn format_answer(part: &PartReport) -> String {
+    match &part.answer {
+        Ok(answer) => answer.to_string(),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+fn serialize_nanos<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2023::registry;
+
+    #[test]
+    fn solve_only_runs_requested_part() {
+        let entry = registry::get(2023, 1).unwrap();
+        let report = solve(entry, "1abc2", Some(1)).unwrap();
+        assert_eq!(report.day, 1);
+        assert_eq!(report.part_1.unwrap().answer.unwrap(), Answer::Int(12));
+        assert!(report.part_2.is_none());
+
+        let report = solve(entry, "1abc2", Some(2)).unwrap();
+        assert!(report.part_1.is_none());
+        assert_eq!(report.part_2.unwrap().answer.unwrap(), Answer::Int(12));
+    }
+
+    #[test]
+    fn solve_reports_errors_per_part() {
+        let entry = registry::get(2023, 1).unwrap();
+        let report = solve(entry, "abc", None).unwrap();
+        assert!(!report.is_ok());
+        let error = report.part_1.unwrap().answer.unwrap_err();
+        assert_eq!(error.day, 1);
+        assert_eq!(error.location.unwrap().line, 1);
+    }
+
+    #[test]
+    fn total_sums_all_stages() {
+        let report = sample_report();
+        assert_eq!(report.total(), Duration::from_nanos(1_110));
+    }
+
+    #[test]
+    fn summarize_parallel_names_slowest_day() {
+        let mut slow = sample_report();
+        slow.day = 23;
+        slow.parse = Duration::from_nanos(8_900);
+        let reports = [sample_report(), slow];
+        let summary = summarize_parallel(&reports, Duration::from_nanos(10_000)).unwrap();
+        assert_eq!(
+            summary,
+            "Solved 2 day(s) in 10.00µs wall-clock; summed per-day wall-clock 11.11µs \
+             (111% of wall-clock); slowest: Day 23 at 10.00µs (100% of wall-clock)"
+        );
+    }
+
+    #[test]
+    fn summarize_parallel_requires_reports() {
+        assert!(summarize_parallel(&[], Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn render_json_reports_nanoseconds() {
+        let json = render(&[sample_report()], ReportFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["total_ns"], 1_110);
+        assert_eq!(value["days"][0]["day"], 1);
+        assert_eq!(value["days"][0]["parse_ns"], 10);
+        assert_eq!(value["days"][0]["part_1"]["answer"], 142);
+        assert_eq!(value["days"][0]["part_1"]["time_ns"], 100);
+        assert_eq!(value["days"][0]["part_2"]["time_ns"], 1_000);
+    }
+
+    #[test]
+    fn render_json_reports_errors() {
+        let mut report = sample_report();
+        report.part_2.as_mut().unwrap().answer = Err(PuzzleError::new(1, "no digits"));
+        let json = render(&[report], ReportFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["days"][0]["part_1"]["answer"], 142);
+        assert_eq!(value["days"][0]["part_2"]["error"], "Day 01: no digits");
+        assert!(value["days"][0]["part_2"].get("answer").is_none());
+    }
+
+    #[test]
+    fn render_csv_has_one_row_per_day() {
+        let csv = render(&[sample_report()], ReportFormat::Csv);
+        assert_eq!(
+            csv,
+            "day,parse_ns,part_1_ns,part_2_ns,total_ns,part_1_answer,part_2_answer\n\
+             1,10,100,1000,1110,142,281"
+        );
+    }
+
+    #[test]
+    fn render_table_includes_totals() {
+        let table = render(&[sample_report()], ReportFormat::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].starts_with("01"));
+        assert!(lines[3].starts_with("Total"));
+        assert!(lines[3].contains("1.11µs"));
+    }
+
+    fn sample_report() -> DayReport {
+        DayReport {
+            day: 1,
+            parse: Duration::from_nanos(10),
+            part_1: Some(PartReport {
+                answer: Ok(Answer::Int(142)),
+                duration: Duration::from_nanos(100),
+            }),
+            part_2: Some(PartReport {
+                answer: Ok(Answer::Int(281)),
+                duration: Duration::from_nanos(1_000),
+            }),
+        }
+    }
+}